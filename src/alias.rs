@@ -0,0 +1,178 @@
+//! Alias tables for drawing weighted lines in constant time.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+/// A table for drawing one of a key's lines in O(1) by its weight, using
+/// Vose's alias method.
+#[derive(Debug, Clone)]
+pub(crate) struct AliasTable {
+    lines: Vec<String>,
+    /// The chance of keeping each column's own line rather than its alias.
+    probability: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Builds a table from lines and their weights, or `None` if no line has
+    /// any weight. Lines with negative weights are never drawn.
+    pub fn build(candidates: &[(&String, f64)]) -> Option<Self> {
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|(_, weight)| weight.max(0.0))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if !(total > 0.0 && total.is_finite()) {
+            return None;
+        }
+        let n = weights.len();
+        let mut probability: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| probability[i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            alias[s] = l;
+            probability[l] -= 1.0 - probability[s];
+            if probability[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Whatever is left over only misses 1.0 by rounding errors, but a
+        // line without weight must never be kept.
+        for i in large.into_iter().chain(small) {
+            probability[i] = if weights[i] > 0.0 { 1.0 } else { 0.0 };
+        }
+        Some(AliasTable {
+            lines: candidates.iter().map(|(line, _)| (*line).clone()).collect(),
+            probability,
+            alias,
+        })
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> &String {
+        let column = rng.gen_range(0..self.lines.len());
+        if rng.gen::<f64>() < self.probability[column] {
+            &self.lines[column]
+        } else {
+            &self.lines[self.alias[column]]
+        }
+    }
+}
+
+/// Alias tables built on the first weighted draw from each key, and sorted
+/// lines on the first unweighted one, dropped when the key's lines or weights
+/// change.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AliasTables {
+    tables: HashMap<String, AliasTable>,
+    /// Sorted so a seeded RNG draws the same line regardless of hash order.
+    sorted: HashMap<String, Vec<String>>,
+}
+
+impl AliasTables {
+    /// Gets the table of `key`, building it from `candidates` if needed.
+    pub fn get_or_build<'a>(
+        &mut self,
+        key: &str,
+        candidates: impl FnOnce() -> Vec<(&'a String, f64)>,
+    ) -> Option<&AliasTable> {
+        if !self.tables.contains_key(key) {
+            let table = AliasTable::build(&candidates())?;
+            self.tables.insert(key.to_string(), table);
+        }
+        self.tables.get(key)
+    }
+
+    /// Gets the sorted `lines` of `key`, sorting them if needed.
+    pub fn get_or_sort(&mut self, key: &str, lines: &HashSet<String>) -> &[String] {
+        self.sorted.entry(key.to_string()).or_insert_with(|| {
+            let mut sorted: Vec<String> = lines.iter().cloned().collect();
+            sorted.sort_unstable();
+            sorted
+        })
+    }
+
+    pub fn invalidate(&mut self, key: &str) {
+        self.tables.remove(key);
+        self.sorted.remove(key);
+    }
+
+    pub fn clear(&mut self) {
+        self.tables.clear();
+        self.sorted.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn alias_sampling() {
+        let (common, rare, never) = (
+            "common".to_string(),
+            "rare".to_string(),
+            "never".to_string(),
+        );
+        let table = AliasTable::build(&[(&common, 3.0), (&rare, 1.0), (&never, 0.0)]).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = HashMap::new();
+        for _ in 0..4000 {
+            *counts.entry(table.sample(&mut rng).as_str()).or_insert(0) += 1;
+        }
+        assert!((2700..3300).contains(&counts["common"]), "{:?}", counts);
+        assert!(!counts.contains_key("never"));
+        assert!(AliasTable::build(&[(&never, 0.0)]).is_none());
+    }
+
+    #[test]
+    fn never_draw_zero_weights() {
+        let lines: Vec<String> = (0..7).map(|i| i.to_string()).collect();
+        let weights = [0.0, 0.1, 0.0, 0.7, 0.2, 0.0, 1e-9];
+        let candidates: Vec<(&String, f64)> = lines.iter().zip(weights.iter().copied()).collect();
+        let table = AliasTable::build(&candidates).unwrap();
+        for (column, weight) in weights.iter().enumerate() {
+            if *weight == 0.0 {
+                assert_eq!(table.probability[column], 0.0);
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..2000 {
+            let line = table.sample(&mut rng);
+            assert!(!["0", "2", "5"].contains(&line.as_str()), "{}", line);
+        }
+    }
+
+    #[test]
+    fn rebuild_on_change() {
+        let mut ht = crate::HotText::new(rand::thread_rng());
+        ht.entry("greet").push_line("Hi!").unwrap().set_weight(0.0);
+        ht.entry("greet").push_line("Hello!").unwrap();
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        ht.entry("greet").push_line("Hi!").unwrap().set_weight(1.0);
+        ht.entry("greet")
+            .push_line("Hello!")
+            .unwrap()
+            .set_weight(0.0);
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hi!");
+        ht.load("./test_includes/common.json").unwrap();
+        assert!(ht.get_line_raw("greet").is_some());
+    }
+
+    #[test]
+    fn resort_on_change() {
+        let mut ht = crate::HotText::new(StdRng::seed_from_u64(3));
+        ht.insert("greet", "Hi!").unwrap();
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hi!");
+        ht.remove("greet", "Hi!").unwrap();
+        ht.insert("greet", "Hello!").unwrap();
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        Extend::extend(&mut ht, vec![("greet", "Hey!")]);
+        ht.remove("greet", "Hello!").unwrap();
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hey!");
+    }
+}
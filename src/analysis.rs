@@ -0,0 +1,227 @@
+//! Analysis passes over loaded content.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// A pair of identical or near-identical lines found under different keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    pub first: (String, String),
+    pub second: (String, String),
+    /// `1.0` when the lines are equal after normalization.
+    pub similarity: f64,
+}
+
+/// Lowercases, drops punctuation and collapses whitespace, so that lines
+/// differing only in those respects compare equal.
+fn normalize(line: &str) -> String {
+    line.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The number of single-character edits needed to turn `a` into `b`.
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// The edit distance between `a` and `b`, or `None` if it is over `max`.
+/// Only a band `max` wide around the diagonal is computed, and it gives up as
+/// soon as a whole row is over `max`.
+fn levenshtein_within(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let over = max + 1;
+    let mut previous: Vec<usize> = (0..=b.len()).map(|j| j.min(over)).collect();
+    let mut current = vec![over; b.len() + 1];
+    for (i, ca) in a.iter().enumerate().map(|(i, ca)| (i + 1, ca)) {
+        let (low, high) = (i.saturating_sub(max).max(1), (i + max).min(b.len()));
+        current[0] = i.min(over);
+        if low > 1 {
+            current[low - 1] = over;
+        }
+        let mut row_min = current[0];
+        for j in low..=high {
+            let substitution = previous[j - 1] + usize::from(*ca != b[j - 1]);
+            current[j] = substitution
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(over);
+            row_min = row_min.min(current[j]);
+        }
+        if high < b.len() {
+            current[high + 1] = over;
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
+
+/// Similarity between `0.0` and `1.0` based on edit distance.
+pub(crate) fn similarity(a: &str, b: &str) -> f64 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / len as f64
+}
+
+impl<R: Rng> HotText<R> {
+    /// Finds lines appearing under more than one key.
+    ///
+    /// Lines are compared after ignoring case, punctuation and extra whitespace.
+    /// With a `threshold` below `1.0`, near-identical lines whose
+    /// [edit-distance](https://en.wikipedia.org/wiki/Levenshtein_distance)
+    /// similarity reaches the threshold are reported as well. Results are sorted
+    /// by key and line.
+    pub fn find_duplicates(&self, threshold: f64) -> Vec<Duplicate> {
+        let mut lines: Vec<(&String, &String, String)> = self
+            .line_pairs
+            .iter()
+            .flat_map(|(key, lines)| lines.iter().map(move |line| (key, line, normalize(line))))
+            .collect();
+        lines.sort();
+
+        let mut duplicates = Vec::new();
+        let mut push = |a: &(&String, &String, String), b: &(&String, &String, String), s| {
+            duplicates.push(Duplicate {
+                first: (a.0.clone(), a.1.clone()),
+                second: (b.0.clone(), b.1.clone()),
+                similarity: s,
+            })
+        };
+
+        if threshold >= 1.0 {
+            let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (i, line) in lines.iter().enumerate() {
+                groups.entry(&line.2).or_default().push(i);
+            }
+            for (i, a) in lines.iter().enumerate() {
+                for &j in &groups[a.2.as_str()] {
+                    if j > i && lines[j].0 != a.0 {
+                        push(a, &lines[j], 1.0);
+                    }
+                }
+            }
+        } else {
+            // Lines are compared in order of length, so each line only meets
+            // those too close in length to be ruled out by it alone.
+            let chars: Vec<Vec<char>> = lines.iter().map(|line| line.2.chars().collect()).collect();
+            let mut by_length: Vec<usize> = (0..lines.len()).collect();
+            by_length.sort_by_key(|&i| chars[i].len());
+            // The most edits two lines of up to `len` characters can differ by
+            // and still reach the threshold.
+            let max_edits = |len: usize| ((1.0 - threshold) * len as f64).floor() as usize + 1;
+            let mut pairs = Vec::new();
+            for (n, &i) in by_length.iter().enumerate() {
+                for &j in &by_length[n + 1..] {
+                    let len = chars[j].len();
+                    if len - chars[i].len() > max_edits(len) {
+                        break;
+                    }
+                    if lines[i].0 == lines[j].0 {
+                        continue;
+                    }
+                    let distance = match levenshtein_within(&chars[i], &chars[j], max_edits(len)) {
+                        Some(distance) => distance,
+                        None => continue,
+                    };
+                    let s = if len == 0 {
+                        1.0
+                    } else {
+                        1.0 - distance as f64 / len as f64
+                    };
+                    if s >= threshold {
+                        pairs.push((i.min(j), i.max(j), s));
+                    }
+                }
+            }
+            pairs.sort_by_key(|&(i, j, _)| (i, j));
+            for (i, j, s) in pairs {
+                push(&lines[i], &lines[j], s);
+            }
+        }
+        duplicates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(similarity("same", "same"), 1.0);
+        assert_eq!(normalize("Oh no!  It's a BEAR."), "oh no its a bear");
+    }
+
+    #[test]
+    fn bounded_edit_distance() {
+        let pairs = [
+            ("kitten", "sitting"),
+            ("", "abc"),
+            ("flaw", "lawn"),
+            ("oh no its a bear", "oh no its a bears"),
+            ("abcdef", "fedcba"),
+        ];
+        for (a, b) in pairs {
+            let (a_chars, b_chars): (Vec<char>, Vec<char>) =
+                (a.chars().collect(), b.chars().collect());
+            let distance = levenshtein(a, b);
+            for max in 0..8 {
+                assert_eq!(
+                    levenshtein_within(&a_chars, &b_chars, max),
+                    Some(distance).filter(|&d| d <= max),
+                    "{:?} {:?} {}",
+                    a,
+                    b,
+                    max
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn find_duplicates() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("combat.encounter", "Oh no! It's a bear!")
+            .unwrap();
+        ht.insert("combat.ambush", "Oh no, it's a bear.").unwrap();
+        ht.insert("combat.ambush", "Oh no! It's a bears!").unwrap();
+        ht.insert("combat.ambush", "Something else entirely.")
+            .unwrap();
+
+        let exact = ht.find_duplicates(1.0);
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].first.0, "combat.ambush");
+        assert_eq!(exact[0].second.0, "combat.encounter");
+
+        let near = ht.find_duplicates(0.9);
+        assert_eq!(near.len(), 2);
+        assert!(near.iter().all(|d| d.first.0 != d.second.0));
+    }
+}
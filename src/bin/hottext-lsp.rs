@@ -0,0 +1,427 @@
+//! A language server for hottext content: completes keys inside
+//! `get_line("…")` and `render_line("…")` calls, jumps from them to the
+//! content file declaring the key, and warns about keys no file declares.
+//!
+//! Content files anywhere under the workspace root are indexed; editors only
+//! need to start `hottext-lsp` for Rust files and the content formats.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use hottext::{scan_declarations, scan_usages, KeyLocation};
+use serde_json::{json, Value};
+
+/// Directories that never hold content worth indexing.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A key declared by a content file.
+struct Declaration {
+    key: String,
+    /// The LSP range of the key, in UTF-16 code units.
+    range: Value,
+}
+
+#[derive(Default)]
+struct Server {
+    /// The keys declared by each content file.
+    declarations: HashMap<PathBuf, Vec<Declaration>>,
+    /// The text of each open document.
+    documents: HashMap<PathBuf, String>,
+}
+
+impl Server {
+    fn index(&mut self, dir: &Path) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+            if file_type.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                    self.index(&path);
+                }
+            } else if file_type.is_symlink() && path.is_dir() {
+                // Symlinked directories can loop back on themselves.
+                continue;
+            } else if let Ok(content) = fs::read_to_string(&path) {
+                self.declare(path, &content);
+            }
+        }
+    }
+
+    fn declare(&mut self, path: PathBuf, content: &str) {
+        let declarations: Vec<Declaration> = scan_declarations(&path, content)
+            .into_iter()
+            .map(|location| Declaration {
+                range: range(content, &location),
+                key: location.key,
+            })
+            .collect();
+        if declarations.is_empty() {
+            self.declarations.remove(&path);
+        } else {
+            self.declarations.insert(path, declarations);
+        }
+    }
+
+    fn is_declared(&self, key: &str) -> bool {
+        self.declarations
+            .values()
+            .any(|keys| keys.iter().any(|declaration| declaration.key == key))
+    }
+
+    fn diagnostics(&self, source: &str) -> Value {
+        let diagnostics: Vec<Value> = scan_usages(source)
+            .into_iter()
+            .filter(|usage| !self.is_declared(&usage.key))
+            .map(|usage| {
+                json!({
+                    "range": range(source, &usage),
+                    "severity": 2,
+                    "source": "hottext",
+                    "message": format!("no content file declares the key `{}`", usage.key),
+                })
+            })
+            .collect();
+        Value::Array(diagnostics)
+    }
+
+    fn completion(&self, source: &str, position: &Value) -> Value {
+        let (line, character) = line_character(position);
+        let text = match source.lines().nth(line) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let before = &text[..byte_offset(text, character)];
+        let quote = match before.rfind('"') {
+            Some(quote) => quote,
+            None => return Value::Null,
+        };
+        // Only complete inside the key argument of a keyed method.
+        let call = format!("{}\"\")", &before[..quote]);
+        if !scan_usages(&call)
+            .iter()
+            .any(|usage| usage.column == before[..quote].chars().count() + 2)
+        {
+            return Value::Null;
+        }
+        let start = before[..quote].encode_utf16().count() + 1;
+        let mut items: Vec<Value> = Vec::new();
+        let mut seen = Vec::new();
+        for (path, keys) in &self.declarations {
+            for declaration in keys {
+                if seen.contains(&&declaration.key) {
+                    continue;
+                }
+                seen.push(&declaration.key);
+                items.push(json!({
+                    "label": declaration.key,
+                    "kind": 21,
+                    "detail": path.display().to_string(),
+                    "textEdit": {
+                        "range": {
+                            "start": {"line": line, "character": start},
+                            "end": {"line": line, "character": character},
+                        },
+                        "newText": declaration.key,
+                    },
+                }));
+            }
+        }
+        Value::Array(items)
+    }
+
+    fn definition(&self, source: &str, position: &Value) -> Value {
+        let (line, character) = line_character(position);
+        let text = source.lines().nth(line).unwrap_or_default();
+        let usage = scan_usages(source).into_iter().find(|usage| {
+            let start = utf16_column(text, usage.column - 1);
+            usage.line == line + 1
+                && start <= character
+                && character <= start + usage.key.encode_utf16().count()
+        });
+        let usage = match usage {
+            Some(usage) => usage,
+            None => return Value::Null,
+        };
+        let locations: Vec<Value> = self
+            .declarations
+            .iter()
+            .flat_map(|(path, keys)| {
+                keys.iter()
+                    .filter(|declaration| declaration.key == usage.key)
+                    .map(
+                        move |declaration| json!({"uri": to_uri(path), "range": declaration.range}),
+                    )
+            })
+            .collect();
+        Value::Array(locations)
+    }
+
+    /// Handles a request, returning its result.
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+        let document = |server: &Self| {
+            let path = from_uri(params["textDocument"]["uri"].as_str().unwrap_or_default());
+            server.documents.get(&path).cloned().unwrap_or_default()
+        };
+        Ok(match method {
+            "initialize" => {
+                let root = params["rootUri"]
+                    .as_str()
+                    .map(from_uri)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+                if let Some(root) = root {
+                    self.index(&root);
+                }
+                json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": {"triggerCharacters": ["\"", "."]},
+                        "definitionProvider": true,
+                    },
+                    "serverInfo": {"name": "hottext-lsp", "version": env!("CARGO_PKG_VERSION")},
+                })
+            }
+            "shutdown" => Value::Null,
+            "textDocument/completion" => self.completion(&document(self), &params["position"]),
+            "textDocument/definition" => self.definition(&document(self), &params["position"]),
+            _ => return Err(format!("`{}` is not supported", method)),
+        })
+    }
+
+    /// Handles a notification, returning the notifications to send back.
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let path = from_uri(uri);
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.open(path, text.to_string())
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                    .unwrap_or_default();
+                self.open(path, text.to_string())
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+                vec![publish(&path, json!([]))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Stores a document's text and rechecks the open Rust documents.
+    fn open(&mut self, path: PathBuf, text: String) -> Vec<Value> {
+        let redeclared = path.extension().is_none_or(|extension| extension != "rs");
+        if redeclared {
+            self.declare(path.clone(), &text);
+        }
+        self.documents.insert(path.clone(), text);
+        self.documents
+            .iter()
+            .filter(|(open, _)| open.extension().is_some_and(|e| e == "rs"))
+            .filter(|(open, _)| redeclared || **open == path)
+            .map(|(open, text)| publish(open, self.diagnostics(text)))
+            .collect()
+    }
+}
+
+fn line_character(position: &Value) -> (usize, usize) {
+    let get = |name: &str| position[name].as_u64().unwrap_or_default() as usize;
+    (get("line"), get("character"))
+}
+
+/// The LSP range of a key found in `text`. LSP counts columns in UTF-16 code
+/// units, where [`KeyLocation`] counts characters.
+fn range(text: &str, location: &KeyLocation) -> Value {
+    let line = text.lines().nth(location.line - 1).unwrap_or_default();
+    let start = utf16_column(line, location.column - 1);
+    let end = start + location.key.encode_utf16().count();
+    json!({
+        "start": {"line": location.line - 1, "character": start},
+        "end": {"line": location.line - 1, "character": end},
+    })
+}
+
+/// The UTF-16 offset of the character at `column` in `line`.
+fn utf16_column(line: &str, column: usize) -> usize {
+    line.chars().take(column).map(char::len_utf16).sum()
+}
+
+/// The byte offset of the UTF-16 offset `utf16` in `line`, clamped to its end.
+fn byte_offset(line: &str, utf16: usize) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= utf16 {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn publish(path: &Path, diagnostics: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": to_uri(path), "diagnostics": diagnostics},
+    })
+}
+
+fn from_uri(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+fn to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Reads one `Content-Length` framed message, or `None` at the end of input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        if method == "exit" {
+            break;
+        }
+        match message.get("id") {
+            Some(id) if !method.is_empty() => {
+                let response = match server.request(method, params) {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                    Err(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": METHOD_NOT_FOUND, "message": message},
+                    }),
+                };
+                write_message(&mut output, &response)?;
+            }
+            // Responses to our own requests; none are sent.
+            Some(_) => {}
+            None => {
+                for notification in server.notify(method, params) {
+                    write_message(&mut output, &notification)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_positions() {
+        let source = "let s = \"😀\"; ht.get_line(\"greet\");\n";
+        let usage = &scan_usages(source)[0];
+        assert_eq!(
+            range(source, usage)["start"]["character"],
+            utf16_column(source, usage.column - 1)
+        );
+        assert_eq!(range(source, usage)["start"]["character"], 27);
+        assert_eq!(byte_offset("😀a", 2), 4);
+        assert_eq!(byte_offset("😀a", 9), 5);
+
+        let mut server = Server::default();
+        server.declare(PathBuf::from("lines.toml"), "greet = [\"Hi!\"]\n");
+        let position = json!({"line": 0, "character": 28});
+        assert_eq!(
+            server.definition(source, &position)[0]["uri"],
+            "file://lines.toml"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skip_symlinked_dirs() {
+        let dir = std::env::temp_dir().join("hottext_lsp_index");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lines.toml"), "greet = [\"Hi!\"]\n").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let mut server = Server::default();
+        server.index(&dir);
+        assert_eq!(server.declarations.len(), 1);
+        assert!(server.is_declared("greet"));
+    }
+}
@@ -0,0 +1,180 @@
+//! Sentence-aware capitalization of substituted values.
+
+#[cfg(feature = "templates")]
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// How substituted values are capitalized depending on where they appear.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Capitalization {
+    /// Substitute values as they are.
+    #[default]
+    Preserve,
+    /// Capitalize values at the start of a sentence.
+    SentenceStart,
+    /// Capitalize values at the start of a sentence and lowercase them
+    /// elsewhere, except for acronyms like `FBI`.
+    Sentence,
+}
+
+#[cfg(feature = "templates")]
+#[derive(Debug, Clone)]
+struct CaseCall {
+    name: String,
+    capitalize: bool,
+    variable: String,
+}
+
+/// A line with its plain variable tags swapped for recased variables.
+#[cfg(feature = "templates")]
+#[derive(Debug, Clone)]
+pub(crate) struct Prepared {
+    pub template: String,
+    calls: Vec<CaseCall>,
+}
+
+#[cfg(feature = "templates")]
+impl Prepared {
+    /// Computes the value of every recased variable from the render data.
+    /// Missing values are left out, so they render as empty like any other.
+    pub fn values(&self, data: &HashMap<&str, &str>) -> Vec<(String, String)> {
+        self.calls
+            .iter()
+            .filter_map(|call| {
+                let value = data.get(call.name.as_str())?;
+                let value = if call.capitalize {
+                    capitalize(value)
+                } else {
+                    lowercase(value)
+                };
+                Some((call.variable.clone(), value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "templates")]
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "templates")]
+fn lowercase(value: &str) -> String {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(first), second) if !second.is_some_and(char::is_uppercase) => {
+            first.to_lowercase().chain(second).chain(chars).collect()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Whether text ending with `before` leaves the next word at the start of a sentence.
+#[cfg(feature = "templates")]
+fn at_sentence_start(before: &str) -> bool {
+    let before = before.trim_end_matches(|c: char| c.is_whitespace() || "\"'(“‘".contains(c));
+    before.is_empty() || before.ends_with(['.', '!', '?', '\n'])
+}
+
+/// Swaps plain `{{name}}` tags for recased variables, or returns `None` if
+/// nothing needs recasing.
+#[cfg(feature = "templates")]
+pub(crate) fn prepare(line: &str, capitalization: Capitalization) -> Option<Prepared> {
+    if capitalization == Capitalization::Preserve {
+        return None;
+    }
+    let mut template = String::with_capacity(line.len());
+    let mut calls = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let (before, tag_start) = rest.split_at(start);
+        template.push_str(before);
+        let end = match tag_start.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+        let name = tag_start[2..end].trim();
+        let plain = !name.is_empty()
+            && !name.starts_with(['#', '^', '/', '!', '>', '&', '{', '='])
+            && !name.contains(char::is_whitespace);
+        let capitalize = plain && at_sentence_start(&template);
+        if plain && (capitalize || capitalization == Capitalization::Sentence) {
+            let variable = format!("__case{}", calls.len());
+            template.push_str("{{");
+            template.push_str(&variable);
+            template.push_str("}}");
+            calls.push(CaseCall {
+                name: name.to_string(),
+                capitalize,
+                variable,
+            });
+        } else {
+            template.push_str(&tag_start[..end + 2]);
+        }
+        rest = &tag_start[end + 2..];
+    }
+    template.push_str(rest);
+    if calls.is_empty() {
+        None
+    } else {
+        Some(Prepared { template, calls })
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets how substituted values are capitalized, so data doesn't have to
+    /// be provided in several casings. Defaults to [`Capitalization::Preserve`].
+    pub fn set_capitalization(&mut self, capitalization: Capitalization) {
+        self.capitalization = capitalization;
+        #[cfg(feature = "templates")]
+        if let Some(memo) = &mut self.render_memo {
+            memo.clear();
+        }
+    }
+
+    /// Chainable variant of [`HotText::set_capitalization()`]
+    pub fn with_capitalization(mut self, capitalization: Capitalization) -> Self {
+        self.set_capitalization(capitalization);
+        self
+    }
+}
+
+#[cfg(all(test, feature = "templates"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recase_values() {
+        let mut ht = HotText::new(rand::thread_rng()).with_capitalization(Capitalization::Sentence);
+        ht.insert(
+            "found",
+            "{{item}} found! You picked up {{item}}. \"{{item}}\", {{npc}} says.",
+        )
+        .unwrap();
+        assert_eq!(
+            ht.render_line("found", vec![("item", "A sword"), ("npc", "NPC")])
+                .unwrap(),
+            "A sword found! You picked up a sword. \"A sword\", NPC says."
+        );
+
+        ht.set_capitalization(Capitalization::SentenceStart);
+        assert_eq!(
+            ht.render_line("found", vec![("item", "a sword"), ("npc", "Bob")])
+                .unwrap(),
+            "A sword found! You picked up a sword. \"A sword\", Bob says."
+        );
+
+        ht.set_capitalization(Capitalization::Preserve);
+        assert_eq!(
+            ht.render_line("found", vec![("item", "a sword")]).unwrap(),
+            "a sword found! You picked up a sword. \"a sword\",  says."
+        );
+    }
+}
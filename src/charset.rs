@@ -0,0 +1,151 @@
+//! Character sets and forbidden substrings checked at load time, e.g. for
+//! glyphs a bitmap font can't draw or raw engine escape codes.
+
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::{include, HotText, LoadWarningKind};
+
+/// The characters and substrings allowed in the lines of some keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharsetRule {
+    /// The ranges of characters lines may use, or `None` to allow any.
+    pub allowed: Option<Vec<RangeInclusive<char>>>,
+    /// Substrings lines must not contain.
+    pub forbidden: Vec<String>,
+}
+
+impl CharsetRule {
+    /// Printable ASCII only, from space to `~`.
+    pub fn printable_ascii() -> Self {
+        CharsetRule {
+            allowed: Some(vec![' '..='~']),
+            forbidden: Vec::new(),
+        }
+    }
+
+    /// Any character, but none of the `forbidden` substrings.
+    pub fn forbid(forbidden: &[&str]) -> Self {
+        CharsetRule {
+            allowed: None,
+            forbidden: forbidden.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Checks `line` against the rule, describing the first violation.
+    pub fn check(&self, line: &str) -> Result<(), String> {
+        if let Some(allowed) = &self.allowed {
+            let invalid = line
+                .chars()
+                .find(|c| !allowed.iter().any(|range| range.contains(c)));
+            if let Some(c) = invalid {
+                return Err(format!("unsupported character {:?}", c));
+            }
+        }
+        match self.forbidden.iter().find(|s| line.contains(s.as_str())) {
+            Some(s) => Err(format!("forbidden {:?}", s)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Checks the lines of keys matching `pattern` against `rule`, or removes
+    /// the rule with `None`. Patterns use `*` and `?` wildcards, e.g.
+    /// `ui.*`, and every matching rule applies.
+    ///
+    /// Violations are reported as [`LoadWarning`](crate::LoadWarning)s.
+    pub fn set_charset_rule(&mut self, pattern: &str, rule: Option<CharsetRule>) {
+        match rule {
+            Some(rule) => self.charset_rules.insert(pattern.to_string(), rule),
+            None => self.charset_rules.remove(pattern),
+        };
+    }
+
+    /// Chainable variant of [`HotText::set_charset_rule()`]
+    pub fn with_charset_rule(mut self, pattern: &str, rule: CharsetRule) -> Self {
+        self.set_charset_rule(pattern, Some(rule));
+        self
+    }
+
+    /// Records a warning if `line` breaks a charset rule of `key`.
+    pub(crate) fn check_charset(&mut self, key: &str, line: &str) {
+        let mut rules: Vec<(&String, &CharsetRule)> = self
+            .charset_rules
+            .iter()
+            .filter(|(pattern, _)| include::wildcard_match(pattern, key))
+            .collect();
+        rules.sort_by_key(|(pattern, _)| *pattern);
+        let reason = match rules.iter().find_map(|(_, rule)| rule.check(line).err()) {
+            Some(reason) => reason,
+            None => return,
+        };
+        self.warn(LoadWarningKind::ForbiddenContent {
+            key: key.to_string(),
+            line: line.to_string(),
+            reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_rules() {
+        let rule = CharsetRule {
+            forbidden: vec!["\x1b[".to_string()],
+            ..CharsetRule::printable_ascii()
+        };
+
+        assert!(rule.check("You found 3 gold!").is_ok());
+        assert_eq!(
+            rule.check("Café"),
+            Err("unsupported character 'é'".to_string())
+        );
+        assert!(rule.check("\x1b[31mRed").is_err());
+        assert!(CharsetRule::forbid(&["<br>"]).check("Café").is_ok());
+        assert!(CharsetRule::forbid(&["<br>"]).check("a<br>b").is_err());
+    }
+
+    #[test]
+    fn warn_on_load() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_charset_rule("ui.*", CharsetRule::printable_ascii())
+            .with_charset_rule("*", CharsetRule::forbid(&["%c"]));
+        ht.load_hashmap(
+            vec![
+                ("ui.title", "Déjà vu"),
+                ("lore.intro", "Déjà vu"),
+                ("lore.outro", "%cFin"),
+            ]
+            .into_iter()
+            .map(|(key, line)| {
+                (
+                    key.to_string(),
+                    vec![line.to_string()].into_iter().collect(),
+                )
+            })
+            .collect(),
+        )
+        .unwrap();
+
+        let mut warnings = ht.take_warnings();
+        warnings.sort_by_key(|warning| warning.to_string());
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(
+            &warnings[0].kind,
+            LoadWarningKind::ForbiddenContent { key, .. } if key == "lore.outro"
+        ));
+        assert_eq!(
+            warnings[1].kind,
+            LoadWarningKind::ForbiddenContent {
+                key: "ui.title".to_string(),
+                line: "Déjà vu".to_string(),
+                reason: "unsupported character 'é'".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Composite entries built from draws of several keys.
+
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::HotText;
+#[cfg(feature = "templates")]
+use crate::MissingKeyError;
+
+/// Keys drawn in order and joined with a separator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Composite {
+    pub keys: Vec<String>,
+    pub separator: String,
+}
+
+impl Composite {
+    pub fn new(keys: &[&str], separator: &str) -> Self {
+        Composite {
+            keys: keys.iter().map(|key| key.to_string()).collect(),
+            separator: separator.to_string(),
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Declares a composite entry, e.g. `npc.chatter` made of `npc.greeting`,
+    /// `npc.rumor` and `npc.farewell` joined with spaces.
+    pub fn set_composite(&mut self, name: &str, composite: Composite) {
+        self.composites.insert(name.to_string(), composite);
+    }
+
+    /// Chainable variant of [`HotText::set_composite()`]
+    pub fn with_composite(mut self, name: &str, composite: Composite) -> Self {
+        self.set_composite(name, composite);
+        self
+    }
+
+    /// Renders a composite entry, drawing one line from each of its keys and
+    /// formatting all of them with the provided data.
+    #[cfg(feature = "templates")]
+    pub fn render_composite<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        name: &str,
+        data: D,
+    ) -> Result<String, Box<dyn Error>> {
+        let data: Vec<(&str, &str)> = data.into_iter().collect();
+        let composite = match self.composites.get(name) {
+            Some(composite) => composite.clone(),
+            None => {
+                let error = MissingKeyError {
+                    key: name.to_string(),
+                };
+                return self.track_miss(name, &data, Err(error.into()));
+            }
+        };
+        let parts = composite
+            .keys
+            .iter()
+            .map(|key| self.render_line(key, data.iter().copied()))
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(parts.join(&composite.separator))
+    }
+}
+
+#[cfg(all(test, feature = "templates"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_composite() {
+        let mut ht = HotText::new(rand::thread_rng()).with_composite(
+            "npc.chatter",
+            Composite::new(&["npc.greeting", "npc.rumor", "npc.farewell"], " "),
+        );
+        ht.insert("npc.greeting", "Hail, {{name}}.").unwrap();
+        ht.insert("npc.rumor", "Wolves in the woods.").unwrap();
+        ht.insert("npc.farewell", "Farewell.").unwrap();
+
+        assert_eq!(
+            ht.render_composite("npc.chatter", vec![("name", "Jake")])
+                .unwrap(),
+            "Hail, Jake. Wolves in the woods. Farewell."
+        );
+
+        ht.set_miss_log(Some(4));
+        let error = ht.render_composite("missing", vec![]).unwrap_err();
+        assert!(error.is::<MissingKeyError>());
+        let misses = ht.misses();
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].key, "missing");
+        assert_eq!(misses[0].kind, crate::MissKind::MissingKey);
+    }
+}
@@ -0,0 +1,135 @@
+//! Key naming conventions checked at load time.
+
+use rand::Rng;
+
+use crate::{HotText, LoadWarningKind};
+
+/// A naming convention for keys, such as `combat.encounter.dragon`.
+///
+/// Keys are split into segments on `separator`. Each segment must be non-empty
+/// and consist of ASCII digits, lowercase letters (or any case, with
+/// `allow_uppercase`) and `extra_chars`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConvention {
+    pub separator: char,
+    pub max_depth: Option<usize>,
+    pub allow_uppercase: bool,
+    pub extra_chars: String,
+}
+
+impl KeyConvention {
+    /// Lowercase, dot-separated segments that may also contain `_` and `-`.
+    pub fn lowercase_dotted() -> Self {
+        KeyConvention {
+            separator: '.',
+            max_depth: None,
+            allow_uppercase: false,
+            extra_chars: "_-".to_string(),
+        }
+    }
+
+    /// Checks `key` against the convention, describing the first violation.
+    pub fn check(&self, key: &str) -> Result<(), String> {
+        let segments: Vec<&str> = key.split(self.separator).collect();
+        if let Some(max_depth) = self.max_depth {
+            if segments.len() > max_depth {
+                return Err(format!(
+                    "{} segments, more than the maximum of {}",
+                    segments.len(),
+                    max_depth
+                ));
+            }
+        }
+        for segment in segments {
+            if segment.is_empty() {
+                return Err("empty segment".to_string());
+            }
+            let invalid = segment.chars().find(|&c| {
+                !(c.is_ascii_digit()
+                    || c.is_ascii_lowercase()
+                    || (self.allow_uppercase && c.is_ascii_uppercase())
+                    || self.extra_chars.contains(c))
+            });
+            if let Some(c) = invalid {
+                return Err(format!(
+                    "invalid character {:?} in segment `{}`",
+                    c, segment
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the naming convention that loaded keys are checked against, or
+    /// disables the check with `None`.
+    ///
+    /// Violations are reported as [`LoadWarning`](crate::LoadWarning)s.
+    pub fn set_key_convention(&mut self, convention: Option<KeyConvention>) {
+        self.key_convention = convention;
+    }
+
+    /// Chainable variant of [`HotText::set_key_convention()`]
+    pub fn with_key_convention(mut self, convention: KeyConvention) -> Self {
+        self.set_key_convention(Some(convention));
+        self
+    }
+
+    /// Records a warning if `key` violates the key convention.
+    pub(crate) fn check_key_convention(&mut self, key: &str) {
+        let reason = match &self.key_convention {
+            Some(convention) => match convention.check(key) {
+                Ok(()) => return,
+                Err(reason) => reason,
+            },
+            None => return,
+        };
+        self.warn(LoadWarningKind::KeyConvention {
+            key: key.to_string(),
+            reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowercase_dotted() {
+        let convention = KeyConvention {
+            max_depth: Some(3),
+            ..KeyConvention::lowercase_dotted()
+        };
+
+        assert!(convention.check("combat.encounter.dragon").is_ok());
+        assert!(convention.check("npc.shop-keeper_2").is_ok());
+        assert!(convention.check("Combat.Encounter").is_err());
+        assert!(convention.check("combat..encounter").is_err());
+        assert!(convention.check("combat encounter").is_err());
+        assert!(convention.check("a.b.c.d").is_err());
+    }
+
+    #[test]
+    fn warn_on_load() {
+        let mut ht =
+            HotText::new(rand::thread_rng()).with_key_convention(KeyConvention::lowercase_dotted());
+        ht.insert("fine.key", "Hi!").unwrap();
+        ht.load_hashmap(
+            vec![(
+                "Combat_Encounter".to_string(),
+                vec!["A lion!".to_string()].into_iter().collect(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        assert_eq!(ht.warnings().len(), 1);
+        assert!(matches!(
+            &ht.warnings()[0].kind,
+            LoadWarningKind::KeyConvention { key, .. } if key == "Combat_Encounter"
+        ));
+    }
+}
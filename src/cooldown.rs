@@ -0,0 +1,217 @@
+//! Cooldowns and recency penalties keeping recently drawn lines from repeating.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// How long a drawn line is left out of further draws from its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cooldown {
+    /// For the next `n` draws from the same key.
+    Draws(usize),
+    /// Until the duration has passed.
+    Time(Duration),
+}
+
+/// A softer alternative to [`Cooldown`]: drawn lines become less likely for
+/// a while, recovering their full weight gradually.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recency {
+    /// The fraction of weight taken off a line right after it was drawn,
+    /// between `0.0` and `1.0`. Lines always keep a sliver of their weight,
+    /// so a key never runs out of lines to draw.
+    pub penalty: f64,
+    /// The number of draws from the same key over which the penalty wears off.
+    pub draws: usize,
+}
+
+/// The smallest factor a recency penalty reduces a weight by.
+const MIN_RECENCY_FACTOR: f64 = 1e-6;
+
+/// When a line was last drawn.
+#[derive(Debug, Clone, Copy)]
+struct LastDrawn {
+    draw: u64,
+    at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cooldowns {
+    default: Option<Cooldown>,
+    keys: HashMap<String, Cooldown>,
+    recency: Option<Recency>,
+    draws: HashMap<String, u64>,
+    last_drawn: HashMap<String, HashMap<String, LastDrawn>>,
+}
+
+impl Cooldowns {
+    fn cooldown(&self, key: &str) -> Option<Cooldown> {
+        self.keys.get(key).copied().or(self.default)
+    }
+
+    /// Whether any cooldown or recency penalty applies to `key`.
+    pub fn applies(&self, key: &str) -> bool {
+        self.recency.is_some() || self.cooldown(key).is_some()
+    }
+
+    /// Whether `line` is still cooling down after being drawn from `key`.
+    pub fn is_cooling(&self, key: &str, line: &str) -> bool {
+        let last = match self.last_drawn.get(key).and_then(|lines| lines.get(line)) {
+            Some(last) => last,
+            None => return false,
+        };
+        match self.cooldown(key) {
+            Some(Cooldown::Draws(n)) => self.draws[key] - last.draw < n as u64,
+            Some(Cooldown::Time(duration)) => last.at.elapsed() < duration,
+            None => false,
+        }
+    }
+
+    /// Drops the candidates that are cooling down, unless that would drop all of them.
+    pub fn retain<T>(&self, candidates: &mut Vec<T>, key_line: impl Fn(&T) -> (&str, &str)) {
+        if candidates.iter().any(|c| {
+            let (key, line) = key_line(c);
+            !self.is_cooling(key, line)
+        }) {
+            candidates.retain(|c| {
+                let (key, line) = key_line(c);
+                !self.is_cooling(key, line)
+            });
+        }
+    }
+
+    /// The factor applied to the weight of `line` for the recency penalty.
+    pub fn recency_factor(&self, key: &str, line: &str) -> f64 {
+        let (recency, last) = match (
+            self.recency,
+            self.last_drawn.get(key).and_then(|lines| lines.get(line)),
+        ) {
+            (Some(recency), Some(last)) => (recency, last),
+            _ => return 1.0,
+        };
+        let age = (self.draws[key] - last.draw) as f64;
+        let remaining = 1.0 - age / recency.draws.max(1) as f64;
+        (1.0 - recency.penalty.clamp(0.0, 1.0) * remaining.max(0.0)).max(MIN_RECENCY_FACTOR)
+    }
+
+    pub fn record(&mut self, key: &str, line: &str) {
+        if !self.applies(key) {
+            return;
+        }
+        let draw = self.draws.entry(key.to_string()).or_default();
+        *draw += 1;
+        let last = LastDrawn {
+            draw: *draw,
+            at: Instant::now(),
+        };
+        self.last_drawn
+            .entry(key.to_string())
+            .or_default()
+            .insert(line.to_string(), last);
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the cooldown for keys without one of their own, or disables it with
+    /// `None`.
+    ///
+    /// Lines cooling down are left out of draws, unless every line of the key
+    /// is cooling down.
+    pub fn set_cooldown(&mut self, cooldown: Option<Cooldown>) {
+        self.cooldowns.default = cooldown;
+    }
+
+    /// Chainable variant of [`HotText::set_cooldown()`]
+    pub fn with_cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.set_cooldown(Some(cooldown));
+        self
+    }
+
+    /// Sets the cooldown for one key, or falls back to the default with `None`.
+    pub fn set_key_cooldown(&mut self, key: &str, cooldown: Option<Cooldown>) {
+        let key = self.normalize_key(key).into_owned();
+        match cooldown {
+            Some(cooldown) => self.cooldowns.keys.insert(key, cooldown),
+            None => self.cooldowns.keys.remove(&key),
+        };
+    }
+
+    /// Chainable variant of [`HotText::set_key_cooldown()`]
+    pub fn with_key_cooldown(mut self, key: &str, cooldown: Cooldown) -> Self {
+        self.set_key_cooldown(key, Some(cooldown));
+        self
+    }
+
+    /// Sets the recency penalty applied to every key, or disables it with `None`.
+    pub fn set_recency(&mut self, recency: Option<Recency>) {
+        self.cooldowns.recency = recency;
+    }
+
+    /// Chainable variant of [`HotText::set_recency()`]
+    pub fn with_recency(mut self, recency: Recency) -> Self {
+        self.set_recency(Some(recency));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_cooldown() {
+        let mut ht = HotText::new(rand::thread_rng()).with_cooldown(Cooldown::Draws(2));
+        for line in ["A", "B", "C"] {
+            ht.insert("bark", line).unwrap();
+        }
+
+        for _ in 0..10 {
+            let mut lines: Vec<String> = (0..3).map(|_| ht.get_line_raw("bark").unwrap()).collect();
+            lines.sort();
+            assert_eq!(lines, vec!["A", "B", "C"]);
+        }
+    }
+
+    #[test]
+    fn time_cooldown() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_key_cooldown("bark", Cooldown::Time(Duration::from_secs(60)));
+        ht.insert("bark", "A").unwrap();
+        ht.insert("bark", "B").unwrap();
+
+        let first = ht.get_line_raw("bark").unwrap();
+        assert_ne!(ht.get_line_raw("bark").unwrap(), first);
+        // Everything is cooling down, so any line may be drawn.
+        assert!(ht.get_line_raw("bark").is_some());
+    }
+
+    #[test]
+    fn recency_penalty() {
+        let mut ht = HotText::new(rand::thread_rng()).with_recency(Recency {
+            penalty: 1.0,
+            draws: 2,
+        });
+        ht.insert("bark", "A").unwrap();
+        ht.insert("bark", "B").unwrap();
+        ht.insert("bark", "C").unwrap();
+
+        let first = ht.get_line_raw("bark").unwrap();
+        assert_eq!(
+            ht.cooldowns.recency_factor("bark", &first),
+            MIN_RECENCY_FACTOR
+        );
+        assert_ne!(ht.get_line_raw("bark").unwrap(), first);
+        assert_eq!(ht.cooldowns.recency_factor("bark", &first), 0.5);
+
+        let mut ht = HotText::new(rand::thread_rng()).with_recency(Recency {
+            penalty: 1.0,
+            draws: 2,
+        });
+        ht.insert("bark", "A").unwrap();
+        assert!(ht.get_line_raw("bark").is_some());
+        assert!(ht.get_line_raw("bark").is_some());
+    }
+}
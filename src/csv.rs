@@ -0,0 +1,239 @@
+//! CSV content, as exported from spreadsheets.
+//!
+//! The first record is a header row; [`CsvOptions`] maps header names onto the
+//! key, text, weight, tags and locale of each line.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::LineMeta;
+
+#[derive(Debug)]
+pub struct CsvParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CsvParseError on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CsvParseError {}
+
+/// Column mapping used by [`HotText::load_csv()`](crate::HotText::load_csv).
+///
+/// Column names are matched against the header row, ignoring case. Optional
+/// columns that are missing from the header are skipped.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub key: String,
+    pub text: String,
+    pub weight: Option<String>,
+    /// Tags are separated by commas or semicolons within the cell.
+    pub tags: Option<String>,
+    pub locale: Option<String>,
+    /// When set, only rows whose locale cell matches (or is empty) are loaded.
+    pub only_locale: Option<String>,
+    pub delimiter: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            key: "key".to_string(),
+            text: "text".to_string(),
+            weight: Some("weight".to_string()),
+            tags: Some("tags".to_string()),
+            locale: Some("locale".to_string()),
+            only_locale: None,
+            delimiter: ',',
+        }
+    }
+}
+
+/// A line read from a CSV record.
+pub(crate) struct CsvLine {
+    pub key: String,
+    pub text: String,
+    pub meta: LineMeta,
+}
+
+/// Splits CSV content into records of fields, honouring quoted fields.
+///
+/// Each record is returned alongside the line number it starts on.
+fn records(content: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, CsvParseError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' {
+            record.push(std::mem::take(&mut field));
+            if record.iter().any(|f| !f.is_empty()) {
+                records.push((start, std::mem::take(&mut record)));
+            }
+            record.clear();
+            line += 1;
+            start = line;
+        } else if c != '\r' {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(CsvParseError {
+            line: start,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+/// Parses CSV content into lines according to the column mapping.
+pub(crate) fn parse(content: &str, options: &CsvOptions) -> Result<Vec<CsvLine>, CsvParseError> {
+    let mut records = records(content, options.delimiter)?.into_iter();
+    let header = match records.next() {
+        Some((_, header)) => header,
+        None => return Ok(Vec::new()),
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+    };
+    let required = |name: &str| {
+        column(name).ok_or_else(|| CsvParseError {
+            line: 1,
+            message: format!("missing required column `{}`", name),
+        })
+    };
+    let key_column = required(&options.key)?;
+    let text_column = required(&options.text)?;
+    let weight_column = options.weight.as_deref().and_then(column);
+    let tags_column = options.tags.as_deref().and_then(column);
+    let locale_column = options.locale.as_deref().and_then(column);
+
+    let mut lines = Vec::new();
+    for (line, record) in records {
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|c| record.get(c))
+                .map(|f| f.trim())
+                .unwrap_or("")
+        };
+
+        if let Some(only_locale) = &options.only_locale {
+            let locale = cell(locale_column);
+            if !locale.is_empty() && !locale.eq_ignore_ascii_case(only_locale) {
+                continue;
+            }
+        }
+
+        let key = cell(Some(key_column));
+        if key.is_empty() {
+            return Err(CsvParseError {
+                line,
+                message: "empty key".to_string(),
+            });
+        }
+
+        let mut meta = LineMeta::default();
+        let weight = cell(weight_column);
+        if !weight.is_empty() {
+            meta.weight = weight.parse().map_err(|_| CsvParseError {
+                line,
+                message: format!("invalid weight `{}`", weight),
+            })?;
+        }
+        meta.tags = cell(tags_column)
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect::<HashSet<_>>();
+
+        lines.push(CsvLine {
+            key: key.to_string(),
+            text: record.get(text_column).cloned().unwrap_or_default(),
+            meta,
+        });
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_quoted_records() {
+        let records = records("a,\"b, \"\"c\"\"\",d\r\n\n\"multi\nline\",e", ',').unwrap();
+
+        assert_eq!(
+            records[0],
+            (
+                1,
+                vec!["a".to_string(), "b, \"c\"".to_string(), "d".to_string()]
+            )
+        );
+        assert_eq!(
+            records[1],
+            (3, vec!["multi\nline".to_string(), "e".to_string()])
+        );
+        assert!(super::records("\"open", ',').is_err());
+    }
+
+    #[test]
+    fn parse_csv() {
+        let content = "Key,Text,Weight,Tags,Locale\n\
+                       greet,Hello!,2,\"friendly; short\",en\n\
+                       greet,Hallo!,,,de\n\
+                       greet,Hi!,,,\n";
+
+        let lines = parse(content, &CsvOptions::default()).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].meta.weight, 2.0);
+        assert!(lines[0].meta.tags.contains("short"));
+
+        let options = CsvOptions {
+            only_locale: Some("en".to_string()),
+            ..CsvOptions::default()
+        };
+        let lines = parse(content, &options).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.text != "Hallo!"));
+
+        let options = CsvOptions {
+            text: "line".to_string(),
+            ..CsvOptions::default()
+        };
+        assert!(parse(content, &options).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Rendering lines with structured data, for mustache sections.
+
+use std::error::Error;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{HotText, RenderOptions};
+
+impl<R: Rng> HotText<R> {
+    /// Variant of [`HotText::render_line()`] taking any serializable data, such
+    /// as a struct or a `serde_json::Value`, so lines can use booleans, lists and
+    /// nested maps in sections like `{{#has_key}}...{{/has_key}}` and
+    /// `{{#items}}{{name}}{{/items}}`.
+    ///
+    /// Helpers and capitalization apply to top-level string values only.
+    pub fn render_line_data<T: Serialize>(
+        &mut self,
+        key: &str,
+        data: &T,
+    ) -> Result<String, Box<dyn Error>> {
+        let data = mustache::to_data(data)?;
+        let strings: Vec<(String, String)> = match &data {
+            mustache::Data::Map(map) => map
+                .iter()
+                .filter_map(|(name, value)| match value {
+                    mustache::Data::String(value) => Some((name.clone(), value.clone())),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let strings: Vec<(&str, &str)> = strings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let raw_line = self.draw_line(key);
+        let options = RenderOptions {
+            structured: Some(data),
+            ..RenderOptions::default()
+        };
+        self.render_inner(key, raw_line, &strings, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn render_sections() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert(
+            "shop.stock",
+            "{{#open}}In stock: {{#items}}{{name}} ({{count}}) {{/items}}{{/open}}{{^open}}Closed.{{/open}}",
+        )
+        .unwrap();
+        ht.insert(
+            "shop.price",
+            "{{number gold}} gold{{#sale}}, on sale!{{/sale}}",
+        )
+        .unwrap();
+
+        let stock = json!({
+            "open": true,
+            "items": [{ "name": "Rope", "count": 2 }, { "name": "Lamp", "count": 1 }],
+        });
+        assert_eq!(
+            ht.render_line_data("shop.stock", &stock).unwrap(),
+            "In stock: Rope (2) Lamp (1) "
+        );
+        assert_eq!(
+            ht.render_line_data("shop.stock", &json!({ "open": false }))
+                .unwrap(),
+            "Closed."
+        );
+        assert_eq!(
+            ht.render_line_data("shop.price", &json!({ "gold": 1200, "sale": true }))
+                .unwrap(),
+            "1,200 gold, on sale!"
+        );
+        assert!(ht.render_line_data("missing", &json!({})).is_err());
+    }
+
+    #[test]
+    fn memoize_structured_renders() {
+        let mut ht = HotText::new(rand::thread_rng()).with_render_memo(Some(8));
+        ht.insert(
+            "door",
+            "{{#open}}Open{{/open}}{{^open}}Shut{{/open}}, {{name}}.",
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            for (open, expected) in [(true, "Open, Vault."), (false, "Shut, Vault.")] {
+                let data = json!({ "open": open, "name": "Vault" });
+                assert_eq!(ht.render_line_data("door", &data).unwrap(), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Date, time and relative-time template helpers.
+//!
+//! Timestamps in render data are Unix seconds (`1700000000`) or UTC RFC 3339
+//! date-times (`2023-11-14T22:13:20Z`). Everything is formatted in UTC.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+];
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date of a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parses Unix seconds or a UTC RFC 3339 date-time.
+fn parse_timestamp(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+    let error = || format!("`{}` is not a timestamp", value);
+    let value = value.strip_suffix(['Z', 'z']).ok_or_else(error)?;
+    let (date, time) = value
+        .split_once(['T', 't', ' '])
+        .unwrap_or((value, "00:00:00"));
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|part| part.parse().map_err(|_| error()))
+        .collect::<Result<_, _>>()?;
+    let time: Vec<i64> = time
+        .split(':')
+        .map(|part| {
+            part.split('.')
+                .next()
+                .unwrap_or_default()
+                .parse()
+                .map_err(|_| error())
+        })
+        .collect::<Result<_, _>>()?;
+    match (date.as_slice(), time.as_slice()) {
+        ([year, month, day], [hour, minute, second]) => {
+            Ok(days_from_civil(*year, *month, *day) * 86_400 + hour * 3600 + minute * 60 + second)
+        }
+        _ => Err(error()),
+    }
+}
+
+/// Formats a timestamp with `%Y %m %d %e %H %M %S %B %b %A %a %%` directives.
+fn format_timestamp(seconds: i64, format: &str) -> String {
+    let days = seconds.div_euclid(86_400);
+    let time = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let month_name = MONTHS[month as usize - 1];
+    let weekday = WEEKDAYS[days.rem_euclid(7) as usize];
+
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&year.to_string()),
+            Some('m') => out.push_str(&format!("{:02}", month)),
+            Some('d') => out.push_str(&format!("{:02}", day)),
+            Some('e') => out.push_str(&day.to_string()),
+            Some('H') => out.push_str(&format!("{:02}", time / 3600)),
+            Some('M') => out.push_str(&format!("{:02}", time / 60 % 60)),
+            Some('S') => out.push_str(&format!("{:02}", time % 60)),
+            Some('B') => out.push_str(month_name),
+            Some('b') => out.push_str(&month_name[..3]),
+            Some('A') => out.push_str(weekday),
+            Some('a') => out.push_str(&weekday[..3]),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Describes a number of seconds in its largest whole unit, e.g. `3 days`.
+fn describe_duration(seconds: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (7 * 86_400, "week"),
+        (86_400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+    ];
+    let seconds = seconds.abs();
+    let (count, unit) = UNITS
+        .iter()
+        .find(|(size, _)| seconds >= *size)
+        .map_or((seconds, "second"), |(size, unit)| (seconds / size, *unit));
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// Describes `seconds` relative to `now`, e.g. `3 days ago` or `in 2 hours`.
+fn relative(seconds: i64, now: i64) -> String {
+    let delta = now - seconds;
+    if delta.abs() < 60 {
+        "just now".to_string()
+    } else if delta > 0 {
+        format!("{} ago", describe_duration(delta))
+    } else {
+        format!("in {}", describe_duration(delta))
+    }
+}
+
+fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(error) => -(error.duration().as_secs() as i64),
+    }
+}
+
+/// `{{date timestamp ["format"]}}`, e.g. `2023-11-14` or with `"%e %B %Y"`.
+pub(crate) fn date(args: &[String]) -> Result<String, String> {
+    let (value, format) = match args {
+        [value] => (value, "%Y-%m-%d"),
+        [value, format] => (value, format.as_str()),
+        _ => return Err("expected a timestamp and an optional format".to_string()),
+    };
+    Ok(format_timestamp(parse_timestamp(value)?, format))
+}
+
+/// `{{ago timestamp}}`, e.g. `3 days ago` or `in 2 hours`.
+pub(crate) fn ago(args: &[String]) -> Result<String, String> {
+    match args {
+        [value] => Ok(relative(parse_timestamp(value)?, now())),
+        _ => Err("expected a timestamp".to_string()),
+    }
+}
+
+/// `{{duration seconds}}`, e.g. `3 days`.
+pub(crate) fn duration(args: &[String]) -> Result<String, String> {
+    match args {
+        [value] => {
+            let seconds: f64 = value
+                .parse()
+                .map_err(|_| format!("`{}` is not a number of seconds", value))?;
+            Ok(describe_duration(seconds as i64))
+        }
+        _ => Err("expected a number of seconds".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_dates() {
+        let seconds = parse_timestamp("2023-11-14T22:13:20Z").unwrap();
+        assert_eq!(seconds, 1_700_000_000);
+        assert_eq!(civil_from_days(days_from_civil(2000, 2, 29)), (2000, 2, 29));
+        assert_eq!(
+            format_timestamp(seconds, "%a %e %b %Y, %H:%M:%S"),
+            "Tue 14 Nov 2023, 22:13:20"
+        );
+        assert_eq!(date(&["-86400".to_string()]).unwrap(), "1969-12-31");
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn relative_times() {
+        assert_eq!(relative(0, 3 * 86_400 + 5), "3 days ago");
+        assert_eq!(relative(7200, 0), "in 2 hours");
+        assert_eq!(relative(0, 30), "just now");
+        assert_eq!(describe_duration(60), "1 minute");
+        assert_eq!(duration(&["90".to_string()]).unwrap(), "1 minute");
+    }
+}
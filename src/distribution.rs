@@ -0,0 +1,139 @@
+//! Checking that draws follow line weights, for catching selection
+//! regressions in tests.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{layer, HotText};
+
+/// The standard normal quantile for a significance level of 0.001.
+const Z_CRITICAL: f64 = 3.090_232;
+
+/// Draws from a key that don't match its weights, from
+/// [`HotText::check_distribution()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistributionError {
+    pub key: String,
+    /// The chi-square statistic of the draws.
+    pub statistic: f64,
+    /// The largest statistic that would have passed.
+    pub critical: f64,
+    /// How often each line was drawn.
+    pub observed: Vec<(String, usize)>,
+}
+
+impl fmt::Display for DistributionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DistributionError: draws from `{}` don't match its weights (chi-square {:.2} > {:.2}), drawn: {:?}",
+            self.key, self.statistic, self.critical, self.observed
+        )
+    }
+}
+
+impl std::error::Error for DistributionError {}
+
+/// The chi-square value a statistic with `df` degrees of freedom exceeds with
+/// a probability of 0.001, by the Wilson-Hilferty approximation.
+fn chi_square_critical(df: usize) -> f64 {
+    let df = df as f64;
+    let c = 2.0 / (9.0 * df);
+    df * (1.0 - c + Z_CRITICAL * c.sqrt()).powi(3)
+}
+
+impl<R: Rng> HotText<R> {
+    /// Draws `samples` lines from a key and checks with a chi-square test
+    /// that each line came up in proportion to its weight, failing at a
+    /// significance level of 0.001 so correct selection almost never fails.
+    ///
+    /// Draws are recorded like any other, so cooldowns and recency penalties
+    /// should be off. Lines without weight must never be drawn.
+    pub fn check_distribution(
+        &mut self,
+        key: &str,
+        samples: usize,
+    ) -> Result<(), DistributionError> {
+        let resolved = self.resolve_key(key).unwrap_or_else(|| key.to_string());
+        let mut candidates =
+            layer::candidates(&self.line_pairs, &self.line_meta, &self.layers, &resolved);
+        self.overrides
+            .apply(&self.line_meta, &self.layers, &resolved, &mut candidates);
+        let expected: Vec<(String, f64)> = candidates
+            .into_iter()
+            .map(|(line, weight)| (line.clone(), weight.max(0.0)))
+            .collect();
+        let total: f64 = expected.iter().map(|(_, weight)| weight).sum();
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for _ in 0..samples {
+            if let Some(line) = self.draw_line(key) {
+                *counts.entry(line).or_default() += 1;
+            }
+        }
+        let mut observed: Vec<(String, usize)> = expected
+            .iter()
+            .map(|(line, _)| (line.clone(), counts.get(line).copied().unwrap_or(0)))
+            .collect();
+
+        let mut statistic = 0.0;
+        let mut drawn_unweighted = counts.values().sum::<usize>() != samples;
+        for ((_, weight), (_, count)) in expected.iter().zip(&observed) {
+            if *weight > 0.0 {
+                let expected = samples as f64 * weight / total;
+                statistic += (*count as f64 - expected).powi(2) / expected;
+            } else if *count > 0 {
+                drawn_unweighted = true;
+            }
+        }
+        let df = expected.iter().filter(|(_, weight)| *weight > 0.0).count();
+        let critical = chi_square_critical(df.saturating_sub(1).max(1));
+        if drawn_unweighted || statistic > critical {
+            observed.sort_by(|(a, _), (b, _)| a.cmp(b));
+            return Err(DistributionError {
+                key: key.to_string(),
+                statistic,
+                critical,
+                observed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Panics unless [`HotText::check_distribution()`] passes.
+    pub fn assert_distribution(&mut self, key: &str, samples: usize) {
+        if let Err(error) = self.check_distribution(key, samples) {
+            panic!("{}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Selection;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn check_weights() {
+        let mut ht = HotText::new(StdRng::seed_from_u64(3));
+        ht.entry("loot")
+            .push_line("Copper")
+            .unwrap()
+            .set_weight(6.0)
+            .push_line("Silver")
+            .unwrap()
+            .set_weight(3.0)
+            .push_line("Gold")
+            .unwrap();
+        ht.assert_distribution("loot", 2000);
+
+        ht.set_selection(Selection::First);
+        let error = ht.check_distribution("loot", 2000).unwrap_err();
+        assert_eq!(error.observed[0], ("Copper".to_string(), 2000));
+        assert!((chi_square_critical(2) - 13.82).abs() < 0.5);
+    }
+}
@@ -0,0 +1,127 @@
+//! Expanding `:sword:`-style shortcodes to emoji.
+
+/// Shortcodes and their emoji, sorted by shortcode.
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("beer", "🍺"),
+    ("bomb", "💣"),
+    ("book", "📖"),
+    ("bow_and_arrow", "🏹"),
+    ("bread", "🍞"),
+    ("broken_heart", "💔"),
+    ("candle", "🕯️"),
+    ("castle", "🏰"),
+    ("check", "✔️"),
+    ("clock", "🕒"),
+    ("coin", "🪙"),
+    ("crown", "👑"),
+    ("cry", "😢"),
+    ("crystal_ball", "🔮"),
+    ("dagger", "🗡️"),
+    ("dragon", "🐉"),
+    ("drop", "💧"),
+    ("fire", "🔥"),
+    ("gem", "💎"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("heart", "❤️"),
+    ("hourglass", "⌛"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("lightning", "⚡"),
+    ("lock", "🔒"),
+    ("mage", "🧙"),
+    ("map", "🗺️"),
+    ("meat", "🍖"),
+    ("moneybag", "💰"),
+    ("moon", "🌙"),
+    ("mushroom", "🍄"),
+    ("potion", "🧪"),
+    ("question", "❓"),
+    ("scroll", "📜"),
+    ("shield", "🛡️"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("smile", "😄"),
+    ("snowflake", "❄️"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("sun", "☀️"),
+    ("sweat_smile", "😅"),
+    ("sword", "⚔️"),
+    ("thinking", "🤔"),
+    ("tree", "🌳"),
+    ("trophy", "🏆"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("wink", "😉"),
+    ("wolf", "🐺"),
+    ("x", "❌"),
+    ("zzz", "💤"),
+];
+
+/// Gets the emoji of a shortcode name, without colons, e.g. `sword` for ⚔️.
+pub fn emoji(name: &str) -> Option<&'static str> {
+    EMOJI
+        .binary_search_by_key(&name, |(shortcode, _)| shortcode)
+        .ok()
+        .map(|i| EMOJI[i].1)
+}
+
+/// Replaces each `:name:` shortcode in `text` with what `expand` returns for
+/// its name. Shortcodes it returns `None` for are left as they are.
+pub(crate) fn expand_shortcodes(text: &str, expand: impl Fn(&str) -> Option<String>) -> String {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-');
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after.find(|c: char| !is_name(c)).unwrap_or(after.len());
+        let name = &after[..name_len];
+        match (after[name_len..].starts_with(':') && !name.is_empty())
+            .then(|| expand(name))
+            .flatten()
+        {
+            Some(expanded) => {
+                out.push_str(&expanded);
+                rest = &after[name_len + 1..];
+            }
+            None => {
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_emoji() {
+        assert!(EMOJI.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(emoji("dragon"), Some("🐉"));
+        assert_eq!(emoji("unicorn_rainbow"), None);
+
+        let unicode = |name: &str| emoji(name).map(str::to_string);
+        assert_eq!(
+            expand_shortcodes("Take this :sword:! Time: 12:30 :nope: ::", unicode),
+            "Take this ⚔️! Time: 12:30 :nope: ::"
+        );
+        assert_eq!(
+            expand_shortcodes(":+1::fire:", |name| Some(format!(
+                "<sprite name=\"{}\">",
+                name
+            ))),
+            "<sprite name=\"+1\"><sprite name=\"fire\">"
+        );
+    }
+}
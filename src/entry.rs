@@ -0,0 +1,134 @@
+//! Builder-style access to the lines of one key.
+
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::{HotText, LineMeta, Selection};
+
+/// A handle for building up the lines of one key, returned by [`HotText::entry()`].
+pub struct KeyEntry<'a, R: Rng> {
+    ht: &'a mut HotText<R>,
+    key: String,
+    line: Option<String>,
+}
+
+impl<'a, R: Rng> KeyEntry<'a, R> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Adds a line to the key. Following calls to [`KeyEntry::set_weight()`]
+    /// and [`KeyEntry::tag()`] apply to it.
+    pub fn push_line(&mut self, line: &str) -> Result<&mut Self, Box<dyn Error>> {
+        let line = self.ht.normalize_line(line);
+        self.ht.insert(&self.key, &line)?;
+        self.line = Some(line);
+        Ok(self)
+    }
+
+    /// Changes the metadata of the line pushed last like
+    /// [`HotText::insert_with_meta()`] would. As the setters can't fail, an
+    /// error writing through is recorded as a warning.
+    fn update_meta(&mut self, update: impl FnOnce(&mut LineMeta)) -> &mut Self {
+        if let Some(line) = &self.line {
+            let meta = self
+                .ht
+                .line_meta
+                .entry(self.key.clone())
+                .or_default()
+                .entry(line.clone())
+                .or_default();
+            update(meta);
+            self.ht.touch(&self.key);
+            self.ht.persist_or_warn();
+        }
+        self
+    }
+
+    /// Sets the weight of the line pushed last. Does nothing before a line is pushed.
+    pub fn set_weight(&mut self, weight: f64) -> &mut Self {
+        self.update_meta(|meta| meta.weight = weight)
+    }
+
+    /// Tags the line pushed last. Does nothing before a line is pushed.
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        self.update_meta(|meta| {
+            meta.tags.insert(tag.to_string());
+        })
+    }
+
+    /// Sets the authored ID of the line pushed last, see [`HotText::line_id()`].
+    /// Does nothing before a line is pushed.
+    pub fn set_id(&mut self, id: &str) -> &mut Self {
+        self.update_meta(|meta| meta.id = Some(id.to_string()))
+    }
+
+    /// Sets how lines of the key are picked, see [`HotText::set_key_selection()`].
+    pub fn set_strategy(&mut self, selection: Selection) -> &mut Self {
+        self.ht.set_key_selection(&self.key, Some(selection));
+        self
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets a handle for adding lines with weights and tags to `key`, e.g.
+    /// `ht.entry("greet").push_line("Hi!")?.set_weight(2.0).tag("casual")`.
+    pub fn entry(&mut self, key: &str) -> KeyEntry<'_, R> {
+        KeyEntry {
+            key: self.normalize_key(key).into_owned(),
+            ht: self,
+            line: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_lines;
+
+    #[test]
+    fn build_entries() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.entry("greet")
+            .set_strategy(Selection::First)
+            .set_weight(5.0)
+            .push_line("Hi!")
+            .unwrap()
+            .set_weight(2.0)
+            .tag("casual")
+            .tag("short")
+            .push_line("Greetings.")
+            .unwrap();
+
+        assert_eq!(get_lines!(ht, "greet").len(), 2);
+        let meta = ht.line_meta("greet", "Hi!").unwrap();
+        assert_eq!(meta.weight, 2.0);
+        assert!(meta.tags.contains("casual") && meta.tags.contains("short"));
+        assert!(ht.line_meta("greet", "Greetings.").is_none());
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Greetings.");
+    }
+
+    #[test]
+    fn track_meta_changes() {
+        let file = std::env::temp_dir().join("hottext_entry_meta.json");
+        let _ = std::fs::remove_file(&file);
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_write_through(&file)
+            .unwrap();
+        ht.entry("greet").push_line("Hi!").unwrap();
+        let generation = ht.generation();
+
+        ht.entry("greet")
+            .push_line("Hi!")
+            .unwrap()
+            .set_weight(3.0)
+            .set_id("greet.hi");
+        assert_eq!(ht.changed_since(generation), vec!["greet"]);
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        let meta = loaded.line_meta("greet", "Hi!").unwrap();
+        assert_eq!(meta.weight, 3.0);
+        assert_eq!(meta.id.as_deref(), Some("greet.hi"));
+    }
+}
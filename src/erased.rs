@@ -0,0 +1,49 @@
+//! A non-generic collection with a boxed RNG, for storing in structs, trait
+//! objects and plugin registries without a type parameter.
+
+use rand::{Rng, RngCore};
+
+use crate::HotText;
+
+/// A [`HotText`] drawing from a boxed RNG, so it can be named without `<R: Rng>`.
+pub type HotTextDyn = HotText<Box<dyn RngCore>>;
+
+impl HotTextDyn {
+    /// Creates an empty collection drawing from `rng`.
+    pub fn new_dyn<R: RngCore + 'static>(rng: R) -> Self {
+        HotText::new(Box::new(rng))
+    }
+}
+
+impl<R: Rng + 'static> HotText<R> {
+    /// Boxes the RNG, keeping the content and settings.
+    pub fn into_dyn(self) -> HotTextDyn {
+        self.map_rng(|rng| Box::new(rng) as Box<dyn RngCore>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    struct Npc {
+        lines: HotTextDyn,
+    }
+
+    #[test]
+    fn erase_rng() {
+        let mut ht = HotText::new(StdRng::seed_from_u64(7));
+        ht.insert("greet", "Hi!").unwrap();
+        let mut npcs = vec![Npc {
+            lines: ht.into_dyn(),
+        }];
+        npcs.push(Npc {
+            lines: HotTextDyn::new_dyn(rand::thread_rng()),
+        });
+
+        assert_eq!(npcs[0].lines.get_line_raw("greet").unwrap(), "Hi!");
+        assert!(npcs[1].lines.get_line_raw("greet").is_none());
+    }
+}
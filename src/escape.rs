@@ -0,0 +1,167 @@
+//! Escaping substituted values for where rendered lines are embedded.
+
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::{HotText, RenderOptions};
+
+/// How values substituted into a line are escaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Escape {
+    /// Mustache's default: `&`, `<`, `>`, `"` and `'` become HTML entities.
+    #[default]
+    Html,
+    /// Values are inserted as they are.
+    Raw,
+    /// Values are escaped to sit inside a JSON string literal.
+    Json,
+    /// Values are quoted as single POSIX shell words.
+    Shell,
+}
+
+impl Escape {
+    /// Escapes one value. [`Escape::Html`] is left to mustache.
+    pub(crate) fn apply(self, value: &str) -> String {
+        match self {
+            Escape::Html | Escape::Raw => value.to_string(),
+            Escape::Json => {
+                let mut escaped = String::with_capacity(value.len());
+                for c in value.chars() {
+                    match c {
+                        '"' => escaped.push_str("\\\""),
+                        '\\' => escaped.push_str("\\\\"),
+                        '\n' => escaped.push_str("\\n"),
+                        '\r' => escaped.push_str("\\r"),
+                        '\t' => escaped.push_str("\\t"),
+                        c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => escaped.push(c),
+                    }
+                }
+                escaped
+            }
+            Escape::Shell => format!("'{}'", value.replace('\'', r"'\''")),
+        }
+    }
+}
+
+/// Turns escaped `{{name}}` tags into unescaped `{{{name}}}` ones, so values
+/// escaped beforehand aren't escaped again as HTML.
+pub(crate) fn unescape_tags(template: &str) -> String {
+    let mut unescaped = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let (before, tag_start) = rest.split_at(start);
+        unescaped.push_str(before);
+        let close = if tag_start.starts_with("{{{") {
+            "}}}"
+        } else {
+            "}}"
+        };
+        let end = match tag_start.find(close) {
+            Some(end) => end + close.len(),
+            None => break,
+        };
+        let tag = &tag_start[..end];
+        let inner = tag[2..end - 2].trim_start();
+        if close == "}}" && !inner.starts_with(&['#', '^', '/', '!', '>', '&', '='][..]) {
+            unescaped.push_str(&format!("{{{}}}", tag));
+        } else {
+            unescaped.push_str(tag);
+        }
+        rest = &tag_start[end..];
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+impl<R: Rng> HotText<R> {
+    /// Variant of [`HotText::render_line()`] that escapes substituted values,
+    /// including helper output, for embedding the line in HTML, a JSON string
+    /// or a shell command. [`HotText::render_line()`] escapes for HTML.
+    ///
+    /// Triple-mustache `{{{name}}}` tags are never escaped.
+    pub fn render_line_escaped<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        data: D,
+        escape: Escape,
+    ) -> Result<String, Box<dyn Error>> {
+        let data: Vec<(&str, &str)> = data.into_iter().collect();
+        let raw_line = self.draw_line(key);
+        let options = RenderOptions {
+            escape,
+            ..RenderOptions::default()
+        };
+        self.render_inner(key, raw_line, &data, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_values() {
+        assert_eq!(
+            unescape_tags("{{a}} {{{b}}} {{& c}} {{#d}}{{e}}{{/d}}"),
+            "{{{a}}} {{{b}}} {{& c}} {{#d}}{{{e}}}{{/d}}"
+        );
+
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("say", "{{name}} says \"{{quote}}\"").unwrap();
+        let data = vec![("name", "Brom <3"), ("quote", "It's \"fine\"\n")];
+        assert_eq!(
+            ht.render_line_escaped("say", data.clone(), Escape::Html)
+                .unwrap(),
+            "Brom &lt;3 says \"It&#39;s &quot;fine&quot;\n\""
+        );
+        assert_eq!(
+            ht.render_line_escaped("say", data.clone(), Escape::Raw)
+                .unwrap(),
+            "Brom <3 says \"It's \"fine\"\n\""
+        );
+        assert_eq!(
+            ht.render_line_escaped("say", data.clone(), Escape::Json)
+                .unwrap(),
+            "Brom <3 says \"It's \\\"fine\\\"\\n\""
+        );
+        assert_eq!(
+            ht.render_line_escaped("say", data, Escape::Shell).unwrap(),
+            "'Brom <3' says \"'It'\\''s \"fine\"\n'\""
+        );
+
+        ht.insert("count", "{{number gold}} for {{name}}").unwrap();
+        assert_eq!(
+            ht.render_line_escaped(
+                "count",
+                vec![("gold", "1000"), ("name", "O'Neil")],
+                Escape::Shell
+            )
+            .unwrap(),
+            "'1,000' for 'O'\\''Neil'"
+        );
+    }
+
+    #[test]
+    fn memoize_per_escape() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_render_memo(Some(8))
+            .with_wrapper("say", "> {{$line}}");
+        ht.insert("say", "{{quote}}").unwrap();
+        let data = vec![("quote", "<hi>")];
+
+        for _ in 0..2 {
+            assert_eq!(
+                ht.render_line_escaped("say", data.clone(), Escape::Raw)
+                    .unwrap(),
+                "> <hi>"
+            );
+            assert_eq!(
+                ht.render_line_escaped("say", data.clone(), Escape::Html)
+                    .unwrap(),
+                "> &lt;hi&gt;"
+            );
+        }
+    }
+}
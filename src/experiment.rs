@@ -0,0 +1,133 @@
+//! A/B experiments assigning users to variants of a key.
+
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::selection::pick_hashed;
+use crate::{hash, HotText};
+
+/// Buckets of users that see the lines of different keys in place of one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Experiment {
+    /// Bucket name, share of users in percent and the key drawn from.
+    pub buckets: Vec<(String, f64, String)>,
+}
+
+impl Experiment {
+    pub fn new() -> Self {
+        Experiment::default()
+    }
+
+    /// Adds a bucket of `percent` of users, drawing from `key`. Percentages
+    /// are relative, so buckets that don't add up to 100 are scaled to fit.
+    pub fn bucket(mut self, name: &str, percent: f64, key: &str) -> Self {
+        self.buckets
+            .push((name.to_string(), percent, key.to_string()));
+        self
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Runs an experiment on `key`, e.g. splitting `shop.greeting` between a
+    /// `control` bucket drawing from itself and a `friendly` bucket drawing
+    /// from `shop.greeting.friendly`.
+    pub fn set_experiment(&mut self, key: &str, experiment: Experiment) {
+        let key = self.normalize_key(key).into_owned();
+        self.experiments.insert(key, experiment);
+    }
+
+    /// Chainable variant of [`HotText::set_experiment()`]
+    pub fn with_experiment(mut self, key: &str, experiment: Experiment) -> Self {
+        self.set_experiment(key, experiment);
+        self
+    }
+
+    /// Ends the experiment on `key`, returning it if there was one.
+    pub fn remove_experiment(&mut self, key: &str) -> Option<Experiment> {
+        self.experiments.remove(self.normalize_key(key).as_ref())
+    }
+
+    /// Gets the bucket `user_id` is assigned to in the experiment on `key`, to
+    /// report alongside analytics. Users always get the same bucket as long as
+    /// the experiment's buckets don't change.
+    pub fn bucket_for(&self, key: &str, user_id: &str) -> Option<&str> {
+        self.bucket_key(key, user_id).map(|(name, _)| name)
+    }
+
+    /// Gets the bucket name and key assigned to `user_id`.
+    fn bucket_key(&self, key: &str, user_id: &str) -> Option<(&str, &str)> {
+        let key = self.normalize_key(key);
+        let experiment = self.experiments.get(key.as_ref())?;
+        let hash = hash::fnv1a(format!("{}\0{}", key, user_id).as_bytes());
+        pick_hashed(&experiment.buckets, |(_, percent, _)| *percent, hash)
+            .map(|(name, _, key)| (name.as_str(), key.as_str()))
+    }
+
+    /// Variant of [`HotText::get_line_raw()`] that draws from the key of the
+    /// bucket `user_id` is assigned to, if `key` has an experiment.
+    pub fn get_line_for_user(&mut self, key: &str, user_id: &str) -> Option<String> {
+        let key = self
+            .bucket_key(key, user_id)
+            .map_or(key, |(_, key)| key)
+            .to_string();
+        self.get_line_raw(&key)
+    }
+
+    /// Variant of [`HotText::render_line()`] that draws from the key of the
+    /// bucket `user_id` is assigned to, if `key` has an experiment.
+    #[cfg(feature = "templates")]
+    pub fn render_line_for_user<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        user_id: &str,
+        data: D,
+    ) -> Result<String, Box<dyn Error>> {
+        let key = self
+            .bucket_key(key, user_id)
+            .map_or(key, |(_, key)| key)
+            .to_string();
+        self.render_line(&key, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assign_buckets() {
+        let mut ht = HotText::new(rand::thread_rng()).with_experiment(
+            "shop.greeting",
+            Experiment::new()
+                .bucket("control", 50.0, "shop.greeting")
+                .bucket("friendly", 50.0, "shop.greeting.friendly"),
+        );
+        ht.insert("shop.greeting", "Welcome.").unwrap();
+        ht.insert("shop.greeting.friendly", "Hey there, friend!")
+            .unwrap();
+
+        let mut friendly = 0;
+        for i in 0..200 {
+            let user = format!("user_{}", i);
+            let bucket = ht.bucket_for("shop.greeting", &user).unwrap().to_string();
+            let line = ht.get_line_for_user("shop.greeting", &user).unwrap();
+            assert_eq!(line == "Hey there, friend!", bucket == "friendly");
+            assert_eq!(ht.bucket_for("shop.greeting", &user).unwrap(), bucket);
+            friendly += (bucket == "friendly") as usize;
+        }
+        assert!((60..140).contains(&friendly), "{}", friendly);
+
+        #[cfg(feature = "templates")]
+        assert!(ht
+            .render_line_for_user("shop.greeting", "user_1", vec![])
+            .is_ok());
+        ht.remove_experiment("shop.greeting");
+        assert!(ht.bucket_for("shop.greeting", "user_1").is_none());
+        assert_eq!(
+            ht.get_line_for_user("shop.greeting", "user_1").unwrap(),
+            "Welcome."
+        );
+    }
+}
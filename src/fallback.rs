@@ -0,0 +1,133 @@
+//! Fallback keys used when a requested key has no lines.
+
+use std::borrow::Cow;
+
+use rand::Rng;
+
+use crate::HotText;
+
+impl<R: Rng> HotText<R> {
+    /// Makes `key` fall back to `fallback` while it has no lines, e.g.
+    /// `combat.encounter.dragon` to `combat.encounter.generic`.
+    ///
+    /// Fallbacks chain, so the fallback may have a fallback of its own.
+    pub fn set_fallback(&mut self, key: &str, fallback: &str) {
+        let key = self.normalize_key(key).into_owned();
+        let fallback = self.normalize_key(fallback).into_owned();
+        self.fallbacks.insert(key, fallback);
+    }
+
+    /// Chainable variant of [`HotText::set_fallback()`]
+    pub fn with_fallback(mut self, key: &str, fallback: &str) -> Self {
+        self.set_fallback(key, fallback);
+        self
+    }
+
+    /// Removes the fallback of `key`, returning it.
+    pub fn remove_fallback(&mut self, key: &str) -> Option<String> {
+        self.fallbacks.remove(self.normalize_key(key).as_ref())
+    }
+
+    /// Whether `key` has lines in the base content or an enabled layer.
+    pub(crate) fn has_lines(&self, key: &str) -> bool {
+        self.line_pairs.contains_key(key)
+            || self
+                .layers
+                .iter()
+                .any(|layer| layer.enabled && layer.line_pairs.contains_key(key))
+    }
+
+    /// Whether `key` has lines to draw, itself or through its fallbacks.
+    pub(crate) fn can_draw(&self, key: &str) -> bool {
+        let resolved = self.resolve_key(key);
+        self.has_lines(resolved.as_deref().unwrap_or(key))
+    }
+
+    /// Gets the `keys` that have no lines, even through their fallbacks, in
+    /// the order given. See [`assert_keys_exist!`](crate::assert_keys_exist)
+    /// for asserting that there are none.
+    pub fn keys_without_lines<I, K>(&self, keys: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<str>,
+    {
+        keys.into_iter()
+            .filter(|key| !self.can_draw(key.as_ref()))
+            .map(|key| key.as_ref().to_string())
+            .collect()
+    }
+
+    /// Follows the fallbacks of a key without lines to the first key that has
+    /// some. Returns `None` when `key` itself should be used.
+    pub(crate) fn resolve_key(&self, key: &str) -> Option<String> {
+        if let Cow::Owned(normalized) = self.normalize_key(key) {
+            return Some(self.resolve_key(&normalized).unwrap_or(normalized));
+        }
+        if self.fallbacks.is_empty() || self.has_lines(key) {
+            return None;
+        }
+        let mut seen = vec![key];
+        let mut current = key;
+        while let Some(fallback) = self.fallbacks.get(current) {
+            if seen.contains(&fallback.as_str()) {
+                return None;
+            }
+            if self.has_lines(fallback) {
+                return Some(fallback.clone());
+            }
+            seen.push(fallback);
+            current = fallback;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_chain() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_fallback("combat.encounter.dragon", "combat.encounter.beast")
+            .with_fallback("combat.encounter.beast", "combat.encounter.generic")
+            .with_fallback("loop.a", "loop.b")
+            .with_fallback("loop.b", "loop.a");
+        ht.insert("combat.encounter.generic", "Something approaches!")
+            .unwrap();
+
+        assert_eq!(
+            ht.get_line_raw("combat.encounter.dragon").unwrap(),
+            "Something approaches!"
+        );
+        ht.insert("combat.encounter.dragon", "A dragon!").unwrap();
+        assert_eq!(
+            ht.get_line_raw("combat.encounter.dragon").unwrap(),
+            "A dragon!"
+        );
+        assert!(ht.get_line_raw("loop.a").is_none());
+    }
+
+    #[test]
+    fn assert_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi!").unwrap();
+        ht.set_fallback("farewell", "greet");
+        const KEYS: &[&str] = &["greet", "farewell"];
+        crate::assert_keys_exist!(ht, KEYS);
+        crate::assert_keys_exist!(ht, ["greet", "farewell"]);
+
+        assert_eq!(
+            ht.keys_without_lines(["shop", "greet", "quest"]),
+            vec!["shop", "quest"]
+        );
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            crate::assert_keys_exist!(ht, ["shop", "greet", "quest"]);
+        }))
+        .unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "2 key(s) have no lines:\n    shop\n    quest"
+        );
+    }
+}
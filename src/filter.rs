@@ -0,0 +1,167 @@
+//! Content filters that can censor or reject rendered lines.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{HotText, Pipeline};
+
+/// What a [`ContentFilter`] decided about a rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Return the line as it is.
+    Allow,
+    /// Return this text instead, e.g. with words censored.
+    Replace(String),
+    /// Fail the render with a [`FilterError`] giving this reason.
+    Reject(String),
+}
+
+/// Checks every rendered line before it is returned.
+///
+/// Implemented for closures taking the line and returning a [`Verdict`].
+pub trait ContentFilter {
+    fn filter(&self, line: &str) -> Verdict;
+}
+
+impl<F: Fn(&str) -> Verdict> ContentFilter for F {
+    fn filter(&self, line: &str) -> Verdict {
+        self(line)
+    }
+}
+
+#[derive(Debug)]
+pub struct FilterError {
+    pub reason: String,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FilterError: {}", self.reason)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// A built-in [`ContentFilter`] matching whole words from a list, ignoring case.
+#[derive(Debug, Clone, Default)]
+pub struct WordFilter {
+    words: HashSet<String>,
+    reject: bool,
+}
+
+impl WordFilter {
+    /// Censors the listed words by replacing their letters with `*`.
+    pub fn censor<'a, I: IntoIterator<Item = &'a str>>(words: I) -> Self {
+        WordFilter {
+            words: words.into_iter().map(str::to_lowercase).collect(),
+            reject: false,
+        }
+    }
+
+    /// Rejects lines containing any of the listed words.
+    pub fn reject<'a, I: IntoIterator<Item = &'a str>>(words: I) -> Self {
+        WordFilter {
+            reject: true,
+            ..WordFilter::censor(words)
+        }
+    }
+}
+
+impl ContentFilter for WordFilter {
+    fn filter(&self, line: &str) -> Verdict {
+        let mut out = String::with_capacity(line.len());
+        let mut matched = None;
+        let mut rest = line;
+        while !rest.is_empty() {
+            let word_len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '\''))
+                .unwrap_or(rest.len());
+            if word_len == 0 {
+                let c = rest.chars().next().expect("rest is not empty");
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let (word, after) = rest.split_at(word_len);
+            if self.words.contains(&word.to_lowercase()) {
+                matched.get_or_insert(word);
+                out.extend(word.chars().map(|_| '*'));
+            } else {
+                out.push_str(word);
+            }
+            rest = after;
+        }
+        match matched {
+            None => Verdict::Allow,
+            Some(word) if self.reject => Verdict::Reject(format!("contains `{}`", word)),
+            Some(_) => Verdict::Replace(out),
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets a single filter that every rendered line passes through before it
+    /// is returned. Shorthand for a [`Pipeline`] with just that filter.
+    pub fn set_content_filter<F: ContentFilter + Send + Sync + 'static>(&mut self, filter: F) {
+        self.set_pipeline(Pipeline::new().filter(filter));
+    }
+
+    /// Chainable variant of [`HotText::set_content_filter()`]
+    pub fn with_content_filter<F: ContentFilter + Send + Sync + 'static>(
+        mut self,
+        filter: F,
+    ) -> Self {
+        self.set_content_filter(filter);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_filters() {
+        let censor = WordFilter::censor(vec!["darn", "heck"]);
+        assert_eq!(
+            censor.filter("Darn it, what the heck? Darning socks."),
+            Verdict::Replace("**** it, what the ****? Darning socks.".to_string())
+        );
+        assert_eq!(censor.filter("All good."), Verdict::Allow);
+        assert_eq!(
+            WordFilter::reject(vec!["heck"]).filter("What the HECK"),
+            Verdict::Reject("contains `HECK`".to_string())
+        );
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn filter_renders() {
+        let mut ht =
+            HotText::new(rand::thread_rng()).with_content_filter(WordFilter::censor(vec!["darn"]));
+        ht.insert("curse", "{{name}}, you darn fool!").unwrap();
+        assert_eq!(
+            ht.render_line("curse", vec![("name", "Jake")]).unwrap(),
+            "Jake, you **** fool!"
+        );
+
+        ht.set_content_filter(|line: &str| {
+            if line.len() > 10 {
+                Verdict::Reject("too long".to_string())
+            } else {
+                Verdict::Allow
+            }
+        });
+        assert!(ht.render_line("curse", vec![("name", "Jake")]).is_err());
+        let mut out = Vec::new();
+        assert!(ht
+            .render_line_to("curse", vec![("name", "Jake")], &mut out)
+            .is_err());
+        assert!(out.is_empty());
+
+        ht.set_pipeline(Pipeline::new());
+        assert!(ht.render_line("curse", vec![("name", "Jake")]).is_ok());
+    }
+}
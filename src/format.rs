@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "json5")]
+use crate::json5;
+#[cfg(feature = "ron")]
+use crate::ron;
+use crate::schema::{self, Content, SchemaError, SCHEMA_VERSION_KEY};
+#[cfg(feature = "json5")]
+use crate::Json5ParseError;
+#[cfg(feature = "ron")]
+use crate::RonParseError;
+use crate::{
+    markdown, text, yaml, LineMeta, LineMetas, LinePairs, TextParseError, YamlParseError,
+    SCHEMA_VERSION,
+};
+
+/// The key/line formats that content files can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    #[cfg(feature = "json5")]
+    Json5,
+    Toml,
+    Yaml,
+    #[cfg(feature = "ron")]
+    Ron,
+    Text,
+    Markdown,
+}
+
+impl Format {
+    /// Picks a format based on the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match extension.as_str() {
+            "json" => Format::Json,
+            #[cfg(feature = "json5")]
+            "json5" | "jsonc" => Format::Json5,
+            "toml" => Format::Toml,
+            "yaml" | "yml" => Format::Yaml,
+            #[cfg(feature = "ron")]
+            "ron" => Format::Ron,
+            "txt" | "text" => Format::Text,
+            "md" | "markdown" => Format::Markdown,
+            _ => return None,
+        })
+    }
+
+    /// Parses `content`, migrating it to the current [schema](crate::SCHEMA_VERSION).
+    /// Structured formats are validated `strict`ly if asked to.
+    pub fn parse(self, content: &str, strict: bool) -> Result<Content, Box<dyn Error>> {
+        let mut duplicates = Vec::new();
+        Ok(match self {
+            Format::Json => schema::from_value(serde_json::from_str(content)?, strict)?,
+            #[cfg(feature = "json5")]
+            Format::Json5 => schema::from_value(json5::parse(content)?, strict)?,
+            Format::Toml => {
+                let value: toml::Value = toml::from_str(content)?;
+                schema::from_value(serde_json::to_value(value)?, strict)?
+            }
+            Format::Yaml => {
+                schema::from_line_pairs(yaml::parse(content, &mut duplicates)?, duplicates)?
+            }
+            #[cfg(feature = "ron")]
+            Format::Ron => schema::from_value(ron::parse(content)?, strict)?,
+            Format::Text => {
+                schema::from_line_pairs(text::parse(content, &mut duplicates)?, duplicates)?
+            }
+            Format::Markdown => {
+                schema::from_line_pairs(markdown::parse(content, &mut duplicates), duplicates)?
+            }
+        })
+    }
+
+    /// Whether the format can hold line metadata, as line objects.
+    pub fn writes_meta(self) -> bool {
+        matches!(self, Format::Json | Format::Toml)
+    }
+
+    /// Serializes key/line pairs sorted by key and line, or returns `None` if
+    /// the format can't be written. Formats that [write metadata](Format::writes_meta())
+    /// write the lines of keys with any metadata as line objects.
+    pub fn serialize(self, line_pairs: &LinePairs, line_meta: &LineMetas) -> Option<String> {
+        let value = || {
+            let mut value = serde_json::json!({ SCHEMA_VERSION_KEY: SCHEMA_VERSION });
+            for (key, lines) in line_pairs {
+                let mut lines: Vec<&String> = lines.iter().collect();
+                lines.sort();
+                value[key] = match line_meta.get(key).filter(|metas| has_meta(metas)) {
+                    // TOML arrays can't mix strings and tables, so every line
+                    // of the key becomes an object.
+                    Some(metas) => lines
+                        .into_iter()
+                        .map(|line| line_object(line, metas.get(line)))
+                        .collect(),
+                    None => serde_json::json!(lines),
+                };
+            }
+            value
+        };
+        // Line-based formats declare their version as a single line.
+        let versioned = || {
+            let mut line_pairs = line_pairs.clone();
+            line_pairs.insert(
+                SCHEMA_VERSION_KEY.to_string(),
+                std::iter::once(SCHEMA_VERSION.to_string()).collect(),
+            );
+            line_pairs
+        };
+        Some(match self {
+            Format::Json => serde_json::to_string_pretty(&value()).expect("JSON values serialize"),
+            // Converted first, as `toml::Value` puts plain values before tables.
+            Format::Toml => toml::Value::try_from(value())
+                .and_then(|value| toml::to_string(&value))
+                .expect("line lists serialize to TOML"),
+            Format::Yaml => yaml::to_string(&versioned()),
+            #[cfg(feature = "ron")]
+            Format::Ron => ron::to_string(line_pairs).replacen(
+                "{\n",
+                &format!("{{\n    \"{}\": {},\n", SCHEMA_VERSION_KEY, SCHEMA_VERSION),
+                1,
+            ),
+            Format::Text => text::to_string(&versioned()),
+            #[cfg(feature = "json5")]
+            Format::Json5 => return None,
+            Format::Markdown => return None,
+        })
+    }
+}
+
+/// Whether any line has metadata other than the default.
+pub(crate) fn has_meta(metas: &HashMap<String, LineMeta>) -> bool {
+    metas.values().any(|meta| *meta != LineMeta::default())
+}
+
+/// A `{ text, weight, tags, id }` line object, leaving out default fields.
+fn line_object(line: &str, meta: Option<&LineMeta>) -> serde_json::Value {
+    let mut object = serde_json::json!({ "text": line });
+    if let Some(meta) = meta {
+        if meta.weight != 1.0 {
+            object["weight"] = serde_json::json!(meta.weight);
+        }
+        if !meta.tags.is_empty() {
+            let mut tags: Vec<&String> = meta.tags.iter().collect();
+            tags.sort();
+            object["tags"] = serde_json::json!(tags);
+        }
+        if let Some(id) = &meta.id {
+            object["id"] = serde_json::json!(id);
+        }
+    }
+    object
+}
+
+/// An error loading a content file, with as much context as can be determined.
+#[derive(Debug)]
+pub struct LoadError {
+    pub file: PathBuf,
+    /// 1-based line of the error, if known.
+    pub line: Option<usize>,
+    /// 1-based column of the error, if known.
+    pub column: Option<usize>,
+    /// The nearest key declared at or before the error, if any.
+    pub key: Option<String>,
+    pub source: Box<dyn Error>,
+}
+
+impl LoadError {
+    /// Wraps an error raised while parsing `content` from `file`.
+    pub(crate) fn new(file: &Path, content: &str, format: Format, source: Box<dyn Error>) -> Self {
+        let (line, column) = position(source.as_ref());
+        let key = match source.downcast_ref::<SchemaError>() {
+            Some(error) => error.key.clone(),
+            None => line.and_then(|line| nearest_key(content, line, format)),
+        };
+        LoadError {
+            file: file.to_path_buf(),
+            line,
+            column,
+            key,
+            source,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}", self.source)?;
+        if let Some(key) = &self.key {
+            write!(f, " (near key `{}`)", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Extracts the line and column of a parse error from any of the supported formats.
+fn position(error: &(dyn Error + 'static)) -> (Option<usize>, Option<usize>) {
+    if let Some(error) = error.downcast_ref::<serde_json::Error>() {
+        return (Some(error.line()), Some(error.column()).filter(|&c| c > 0));
+    }
+    if let Some(error) = error.downcast_ref::<toml::de::Error>() {
+        return match error.line_col() {
+            Some((line, column)) => (Some(line + 1), Some(column + 1)),
+            None => (None, None),
+        };
+    }
+    if let Some(error) = error.downcast_ref::<TextParseError>() {
+        return (Some(error.line), None);
+    }
+    if let Some(error) = error.downcast_ref::<YamlParseError>() {
+        return (Some(error.line), None);
+    }
+    #[cfg(feature = "json5")]
+    if let Some(error) = error.downcast_ref::<Json5ParseError>() {
+        return (Some(error.line), None);
+    }
+    #[cfg(feature = "ron")]
+    if let Some(error) = error.downcast_ref::<RonParseError>() {
+        return (Some(error.line), None);
+    }
+    (None, None)
+}
+
+/// Finds the last key declared on or before `line` (1-based).
+fn nearest_key(content: &str, line: usize, format: Format) -> Option<String> {
+    content
+        .lines()
+        .take(line)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .find_map(|l| declared_key(l.trim_end(), format))
+}
+
+/// Gets the key declared on a line, if it looks like a key declaration.
+pub(crate) fn declared_key(line: &str, format: Format) -> Option<String> {
+    match format {
+        Format::Text => {
+            let trimmed = line.trim();
+            trimmed
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .map(|k| k.trim().to_string())
+        }
+        Format::Markdown => {
+            let trimmed = line.trim_start();
+            trimmed
+                .starts_with('#')
+                .then(|| trimmed.trim_start_matches('#').trim().to_string())
+        }
+        Format::Yaml => {
+            if line.starts_with([' ', '-', '#']) {
+                return None;
+            }
+            line.split_once(':')
+                .map(|(key, _)| key.trim().trim_matches(['"', '\'']).to_string())
+        }
+        Format::Toml => {
+            let (key, _) = line.split_once('=')?;
+            let key = key.trim();
+            if key.starts_with('"') {
+                Some(key.trim_matches('"').to_string())
+            } else {
+                key.chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                    .then(|| key.to_string())
+            }
+        }
+        _ => {
+            // JSON-like formats: a quoted key followed by `:`.
+            let trimmed = line.trim_start();
+            let (key, _) = trimmed.split_once(':')?;
+            let key = key.trim();
+            if key.len() >= 2 && key.starts_with(['"', '\'']) && key.ends_with(['"', '\'']) {
+                Some(key[1..key.len() - 1].to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_error_context() {
+        let content = "{\n    \"meta.welcome\": [\"Hi!\"],\n    \"combat.encounter\": [\n        \"A lion!\"\n        \"A tiger!\"\n    ]\n}\n";
+        let source = Format::Json.parse(content, false).unwrap_err();
+        let error = LoadError::new(Path::new("lines.json"), content, Format::Json, source);
+
+        assert_eq!(error.line, Some(5));
+        assert_eq!(error.key.as_deref(), Some("combat.encounter"));
+        assert!(error.to_string().starts_with("lines.json:5:"));
+    }
+
+    #[test]
+    fn toml_error_context() {
+        let content = "\"meta.welcome\" = [\"Hi!\"]\n\"combat.encounter\" = [\"A lion!\",\n  \"A tiger!\" \"oops\"]\n";
+        let source = Format::Toml.parse(content, false).unwrap_err();
+        let error = LoadError::new(Path::new("lines.toml"), content, Format::Toml, source);
+
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.key.as_deref(), Some("combat.encounter"));
+    }
+}
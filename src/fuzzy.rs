@@ -0,0 +1,80 @@
+//! Looking up lines by approximate keys.
+
+use rand::Rng;
+
+use crate::analysis::similarity;
+use crate::{telemetry, HotText};
+
+/// A line drawn from the key best matching the one asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    /// The key the line was drawn from.
+    pub key: String,
+    /// `1.0` when the key asked for has lines itself.
+    pub similarity: f64,
+    pub line: String,
+}
+
+impl<R: Rng> HotText<R> {
+    /// Variant of [`HotText::get_line_raw()`] for keys derived from user
+    /// input, e.g. a chatbot's `"greting"`. If the key has no lines, even
+    /// through its fallbacks, the line is drawn from the existing key most
+    /// similar to it instead, ignoring case, as long as their
+    /// [edit-distance](https://en.wikipedia.org/wiki/Levenshtein_distance)
+    /// similarity reaches `threshold`. Ties go to the first key in sorted
+    /// order.
+    pub fn get_line_fuzzy(&mut self, key: &str, threshold: f64) -> Option<FuzzyMatch> {
+        if self.can_draw(key) {
+            let line = self.get_line_raw(key)?;
+            return Some(FuzzyMatch {
+                key: key.to_string(),
+                similarity: 1.0,
+                line,
+            });
+        }
+        let wanted = key.trim().to_lowercase();
+        let mut best: Option<(&String, f64)> = None;
+        for candidate in self.drawable_keys() {
+            let score = similarity(&wanted, &candidate.to_lowercase());
+            if score >= threshold && best.is_none_or(|(_, best)| score > best) {
+                best = Some((candidate, score));
+            }
+        }
+        let (matched, similarity) = match best {
+            Some((matched, similarity)) => (matched.clone(), similarity),
+            None => {
+                self.record_miss(key, telemetry::MissKind::MissingKey, &[]);
+                return None;
+            }
+        };
+        let line = self.get_line_raw(&matched)?;
+        Some(FuzzyMatch {
+            key: matched,
+            similarity,
+            line,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_lookup() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greeting", "Hello!").unwrap();
+        ht.insert("farewell", "Goodbye!").unwrap();
+
+        let exact = ht.get_line_fuzzy("greeting", 0.8).unwrap();
+        assert_eq!((exact.key.as_str(), exact.similarity), ("greeting", 1.0));
+
+        let fuzzy = ht.get_line_fuzzy(" Greting", 0.8).unwrap();
+        assert_eq!(fuzzy.key, "greeting");
+        assert_eq!(fuzzy.line, "Hello!");
+        assert!(fuzzy.similarity < 1.0);
+
+        assert!(ht.get_line_fuzzy("weather", 0.8).is_none());
+        assert_eq!(ht.get_line_fuzzy("fairwell", 0.5).unwrap().key, "farewell");
+    }
+}
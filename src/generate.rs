@@ -0,0 +1,151 @@
+//! Generators of arbitrary valid content, for property-testing loaders,
+//! merging and selection.
+//!
+//! The generators draw from any [`Rng`], so they plug into property-testing
+//! frameworks by seeding an RNG from a generated value, e.g. with proptest's
+//! `any::<u64>().prop_map(|seed| generator.line_pairs(&mut StdRng::seed_from_u64(seed)))`.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::{SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+
+const WORDS: [&str; 16] = [
+    "dragon", "sword", "tavern", "ale", "goblin", "quest", "gold", "king", "night", "storm",
+    "café", "über", "ninja", "忍者", "fuego", "🐉",
+];
+
+const PUNCTUATION: [&str; 4] = [".", "!", "?", "..."];
+
+/// Settings for generating content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentGenerator {
+    /// At most this many keys.
+    pub max_keys: usize,
+    /// At least one and at most this many lines per key.
+    pub max_lines: usize,
+    /// At most this many dot-separated segments per key.
+    pub max_key_depth: usize,
+    /// At most this many words per line.
+    pub max_words: usize,
+    /// Whether lines may contain `{{variable}}` tags.
+    pub templates: bool,
+}
+
+impl Default for ContentGenerator {
+    fn default() -> Self {
+        ContentGenerator {
+            max_keys: 8,
+            max_lines: 5,
+            max_key_depth: 3,
+            max_words: 8,
+            templates: true,
+        }
+    }
+}
+
+impl ContentGenerator {
+    /// Generates a `snake_case` dotted key like `combat.encounter`.
+    pub fn key<R: Rng>(&self, rng: &mut R) -> String {
+        let depth = rng.gen_range(1..=self.max_key_depth.max(1));
+        (0..depth)
+            .map(|_| {
+                let word = WORDS[..10].choose(rng).expect("words exist");
+                if rng.gen_bool(0.2) {
+                    format!("{}_{}", word, rng.gen_range(0..10))
+                } else {
+                    word.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Generates a non-empty line without surrounding whitespace.
+    pub fn line<R: Rng>(&self, rng: &mut R) -> String {
+        let count = rng.gen_range(1..=self.max_words.max(1));
+        let mut words: Vec<String> = (0..count)
+            .map(|_| {
+                if self.templates && rng.gen_bool(0.15) {
+                    format!("{{{{{}}}}}", WORDS[..10].choose(rng).expect("words exist"))
+                } else {
+                    WORDS.choose(rng).expect("words exist").to_string()
+                }
+            })
+            .collect();
+        words[0] = capitalize(&words[0]);
+        format!(
+            "{}{}",
+            words.join(" "),
+            PUNCTUATION.choose(rng).expect("punctuation exists")
+        )
+    }
+
+    /// Generates key/line pairs, as taken by [`HotText::load_hashmap()`](crate::HotText::load_hashmap()).
+    pub fn line_pairs<R: Rng>(&self, rng: &mut R) -> HashMap<String, HashSet<String>> {
+        let mut line_pairs: HashMap<String, HashSet<String>> = HashMap::new();
+        for _ in 0..rng.gen_range(0..=self.max_keys) {
+            let key = self.key(rng);
+            let count = rng.gen_range(1..=self.max_lines.max(1));
+            let lines = line_pairs.entry(key).or_default();
+            for _ in 0..count {
+                lines.insert(self.line(rng));
+            }
+        }
+        line_pairs
+    }
+
+    /// Generates the contents of a `.json` content file.
+    pub fn json<R: Rng>(&self, rng: &mut R) -> String {
+        to_json(&self.line_pairs(rng))
+    }
+}
+
+/// Serializes key/line pairs as a content file of the current schema version.
+fn to_json(line_pairs: &HashMap<String, HashSet<String>>) -> String {
+    let mut content = serde_json::to_value(line_pairs).expect("line pairs serialize to JSON");
+    content[SCHEMA_VERSION_KEY] = SCHEMA_VERSION.into();
+    serde_json::to_string_pretty(&content).expect("JSON values serialize")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HotText;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::fs;
+
+    #[test]
+    fn loaded_content_matches_generated() {
+        let generator = ContentGenerator::default();
+        let dir = std::env::temp_dir().join("hottext_generate");
+        fs::create_dir_all(&dir).unwrap();
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let line_pairs = generator.line_pairs(&mut rng);
+
+            let file = dir.join(format!("{}.json", seed));
+            fs::write(&file, to_json(&line_pairs)).unwrap();
+            let mut ht = HotText::new(StdRng::seed_from_u64(seed))
+                .with_load(&file)
+                .unwrap();
+            assert!(ht.warnings().is_empty(), "seed {}", seed);
+
+            for (key, lines) in &line_pairs {
+                assert_eq!(ht.get_lines_raw(key).as_ref(), Some(lines));
+                assert!(lines.contains(&ht.get_line_raw(key).unwrap()));
+            }
+        }
+    }
+}
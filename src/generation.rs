@@ -0,0 +1,82 @@
+//! A generation counter for precise cache invalidation.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// The current generation and the generation each key last changed in.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Generations {
+    current: u64,
+    keys: HashMap<String, u64>,
+}
+
+impl Generations {
+    /// Starts a new generation in which `keys` changed.
+    pub fn bump<'a>(&mut self, keys: impl IntoIterator<Item = &'a str>) {
+        self.current += 1;
+        for key in keys {
+            self.keys.insert(key.to_string(), self.current);
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets the content generation, which increases whenever lines are
+    /// inserted, removed, loaded, patched or restored, or a layer changes.
+    pub fn generation(&self) -> u64 {
+        self.generations.current
+    }
+
+    /// Gets the keys whose lines changed after generation `generation`, sorted,
+    /// so caches built at that generation can invalidate just those keys.
+    pub fn changed_since(&self, generation: u64) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .generations
+            .keys
+            .iter()
+            .filter(|(_, changed)| **changed > generation)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Records that `key` changed, unless a surrounding change is already
+    /// comparing the content before and after.
+    pub(crate) fn touch(&mut self, key: &str) {
+        self.alias_tables.invalidate(key);
+        if !self.observers.observing {
+            self.generations.bump(std::iter::once(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_generations() {
+        let mut ht = HotText::new(rand::thread_rng());
+        assert_eq!(ht.generation(), 0);
+        ht.insert("greet", "Hi!").unwrap();
+        let generation = ht.generation();
+        assert!(ht.changed_since(generation).is_empty());
+
+        ht.insert("bye", "Bye!").unwrap();
+        ht.load_layer("holiday", "./test_includes/common.json")
+            .unwrap();
+        assert!(ht.generation() > generation);
+        assert_eq!(ht.changed_since(generation), vec!["bye", "names"]);
+        assert_eq!(ht.changed_since(0), vec!["bye", "greet", "names"]);
+
+        let generation = ht.generation();
+        ht.insert("greet", "Hi!").unwrap();
+        ht.transaction(|tx| tx.load("./test_includes/cycle_a.json"))
+            .unwrap_err();
+        assert_eq!(ht.generation(), generation);
+    }
+}
@@ -0,0 +1,220 @@
+//! English grammar template helpers.
+
+/// `{{list items ["conjunction"] ["no-serial"]}}`: joins comma-separated
+/// items as `a, b, and c`.
+pub(crate) fn list(args: &[String]) -> Result<String, String> {
+    let (value, options) = args.split_first().ok_or("expected a list")?;
+    let mut conjunction = "and";
+    let mut serial = true;
+    for option in options {
+        match option.as_str() {
+            "no-serial" => serial = false,
+            "serial" => serial = true,
+            other => conjunction = other,
+        }
+    }
+
+    let items: Vec<&str> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect();
+    Ok(match items.as_slice() {
+        [] => String::new(),
+        [item] => item.to_string(),
+        [first, second] => format!("{} {} {}", first, conjunction, second),
+        [init @ .., last] => format!(
+            "{}{} {} {}",
+            init.join(", "),
+            if serial { "," } else { "" },
+            conjunction,
+            last
+        ),
+    })
+}
+
+/// Whether a word is read starting with a vowel sound.
+fn starts_with_vowel_sound(word: &str) -> bool {
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let first = match word.chars().next() {
+        Some(first) => first,
+        None => return false,
+    };
+
+    if first.is_ascii_digit() {
+        // "an 8", "an 11", "an 18,000" but "a 1", "a 110".
+        let digits: String = word.chars().take_while(char::is_ascii_digit).collect();
+        return digits.starts_with('8')
+            || ((digits.starts_with("11") || digits.starts_with("18")) && digits.len() % 3 == 2);
+    }
+
+    let letters: String = word.chars().take_while(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.chars().all(|c| c.is_uppercase()) {
+        // Acronyms are read letter by letter: "an FBI agent", "a UFO".
+        return "AEFHILMNORSX".contains(first);
+    }
+
+    let lower = word.to_lowercase();
+    const VOWEL_SOUND: [&str; 6] = ["hour", "honest", "honor", "honour", "heir", "unin"];
+    const CONSONANT_SOUND: [&str; 12] = [
+        "uni", "use", "usu", "uti", "ure", "uri", "ufo", "eu", "ewe", "one", "once", "ouija",
+    ];
+    if VOWEL_SOUND.iter().any(|prefix| lower.starts_with(prefix)) {
+        return true;
+    }
+    if CONSONANT_SOUND
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+    {
+        return false;
+    }
+    matches!(lower.chars().next(), Some('a' | 'e' | 'i' | 'o' | 'u'))
+}
+
+/// `{{a word}}` (or `{{an word}}`): prefixes a word with `a` or `an`, e.g. `an apple`.
+pub(crate) fn article(args: &[String]) -> Result<String, String> {
+    match args {
+        [word] => Ok(format!(
+            "{} {}",
+            if starts_with_vowel_sound(word) {
+                "an"
+            } else {
+                "a"
+            },
+            word
+        )),
+        _ => Err("expected a word".to_string()),
+    }
+}
+
+/// `{{possessive name}}`: `Jake's`, or `James'` for names ending in `s`.
+pub(crate) fn possessive(args: &[String]) -> Result<String, String> {
+    match args {
+        [name] if name.ends_with(['s', 'S']) => Ok(format!("{}'", name)),
+        [name] => Ok(format!("{}'s", name)),
+        _ => Err("expected a name".to_string()),
+    }
+}
+
+/// The subject, object, possessive determiner, possessive pronoun and
+/// reflexive forms of a pronoun set.
+fn pronoun_forms(value: &str) -> Result<Vec<String>, String> {
+    let forms: Vec<&str> = value.split('/').map(str::trim).collect();
+    if forms.len() == 5 {
+        return Ok(forms.into_iter().map(str::to_string).collect());
+    }
+    let forms: &[&str] = match forms[0].to_lowercase().as_str() {
+        "he" | "him" | "his" => &["he", "him", "his", "his", "himself"],
+        "she" | "her" | "hers" => &["she", "her", "her", "hers", "herself"],
+        "they" | "them" | "their" => &["they", "them", "their", "theirs", "themselves"],
+        "it" | "its" => &["it", "it", "its", "its", "itself"],
+        _ => {
+            return Err(format!(
+                "unknown pronouns `{}`, use a set like `xe/xem/xyr/xyrs/xemself`",
+                value
+            ))
+        }
+    };
+    Ok(forms.iter().map(|form| form.to_string()).collect())
+}
+
+/// `{{they pronouns}}`, `{{them ...}}`, `{{their ...}}`, `{{theirs ...}}` and
+/// `{{themselves ...}}`: the form of the pronouns in the data matching the
+/// helper's name.
+pub(crate) fn pronoun(form: usize, args: &[String]) -> Result<String, String> {
+    match args {
+        [value] => Ok(pronoun_forms(value)?.swap_remove(form)),
+        _ => Err("expected pronouns such as `she/her`".to_string()),
+    }
+}
+
+/// Whether a subject takes plural verb forms: a count other than one, a list,
+/// or pronouns like `they`.
+fn is_plural(subject: &str) -> bool {
+    let subject = subject.trim();
+    if let Ok(count) = subject.parse::<f64>() {
+        return count.abs() != 1.0;
+    }
+    if subject.contains(',') {
+        return true;
+    }
+    let first = subject.split('/').next().unwrap_or_default().to_lowercase();
+    matches!(first.as_str(), "they" | "them" | "we" | "you")
+}
+
+/// `{{verb subject "is" "are"}}`: the singular or plural form, depending on a
+/// count, a list or pronouns in the data.
+pub(crate) fn verb(args: &[String]) -> Result<String, String> {
+    match args {
+        [subject, singular, plural] => Ok(if is_plural(subject) {
+            plural.clone()
+        } else {
+            singular.clone()
+        }),
+        _ => Err("expected a subject, a singular and a plural form".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(helper: fn(&[String]) -> Result<String, String>, args: &[&str]) -> String {
+        helper(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn join_lists() {
+        assert_eq!(
+            call(list, &["sword, shield,potion"]),
+            "sword, shield, and potion"
+        );
+        assert_eq!(call(list, &["sword,shield"]), "sword and shield");
+        assert_eq!(call(list, &["a, b, c", "or", "no-serial"]), "a, b or c");
+        assert_eq!(call(list, &["sword"]), "sword");
+        assert_eq!(call(list, &[""]), "");
+    }
+
+    #[test]
+    fn articles() {
+        for (word, expected) in [
+            ("apple", "an apple"),
+            ("sword", "a sword"),
+            ("hour", "an hour"),
+            ("unicorn", "a unicorn"),
+            ("uninvited guest", "an uninvited guest"),
+            ("FBI agent", "an FBI agent"),
+            ("UFO", "a UFO"),
+            ("8-sided die", "an 8-sided die"),
+            ("11th hour", "an 11th hour"),
+            ("110 coins", "a 110 coins"),
+        ] {
+            assert_eq!(call(article, &[word]), expected);
+        }
+    }
+
+    #[test]
+    fn possessives_and_pronouns() {
+        assert_eq!(call(possessive, &["Jake"]), "Jake's");
+        assert_eq!(call(possessive, &["James"]), "James'");
+
+        assert_eq!(pronoun(0, &["she/her".to_string()]).unwrap(), "she");
+        assert_eq!(pronoun(1, &["He".to_string()]).unwrap(), "him");
+        assert_eq!(pronoun(4, &["they".to_string()]).unwrap(), "themselves");
+        assert_eq!(
+            pronoun(2, &["xe/xem/xyr/xyrs/xemself".to_string()]).unwrap(),
+            "xyr"
+        );
+        assert!(pronoun(0, &["???".to_string()]).is_err());
+    }
+
+    #[test]
+    fn verb_agreement() {
+        assert_eq!(call(verb, &["1", "is", "are"]), "is");
+        assert_eq!(call(verb, &["3", "is", "are"]), "are");
+        assert_eq!(call(verb, &["0", "is", "are"]), "are");
+        assert_eq!(call(verb, &["they/them", "was", "were"]), "were");
+        assert_eq!(call(verb, &["she/her", "was", "were"]), "was");
+        assert_eq!(call(verb, &["Jake, Alex", "has", "have"]), "have");
+    }
+}
@@ -0,0 +1,83 @@
+//! The hasher used for the key maps on the lookup path, and a stable hash for
+//! IDs and seeds.
+//!
+//! With the `fast-hash` feature, keys are hashed with an FxHash-style hasher
+//! instead of the standard SipHash. It is several times faster for short keys,
+//! but not resistant to collision attacks, so only enable it for trusted content.
+
+#[cfg(feature = "fast-hash")]
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// 64-bit FNV-1a, chosen over [`std::hash::Hasher`]s because its output is
+/// specified and never changes between releases.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type KeyHasher = std::collections::hash_map::RandomState;
+
+#[cfg(feature = "fast-hash")]
+pub(crate) type KeyHasher = BuildHasherDefault<FxHasher>;
+
+/// The multiply-rotate hash used by rustc.
+#[cfg(feature = "fast-hash")]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FxHasher {
+    hash: u64,
+}
+
+#[cfg(feature = "fast-hash")]
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+#[cfg(feature = "fast-hash")]
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+        for &byte in chunks.remainder() {
+            self.add(u64::from(byte));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(u64::from(i));
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(all(test, feature = "fast-hash"))]
+mod tests {
+    use super::*;
+    use std::hash::BuildHasher;
+
+    fn hash(key: &str) -> u64 {
+        KeyHasher::default().hash_one(key)
+    }
+
+    #[test]
+    fn hash_keys() {
+        assert_eq!(hash("combat.encounter"), hash("combat.encounter"));
+        assert_ne!(hash("combat.encounter"), hash("combat.encountes"));
+        assert_ne!(hash("a"), hash("a\0"));
+    }
+}
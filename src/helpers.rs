@@ -0,0 +1,451 @@
+//! Helpers called from templates as `{{helper arg...}}`.
+//!
+//! Arguments are names looked up in the render data, or quoted (`"is"`) and
+//! numeric (`3`) literals. Before a line is compiled, every helper tag is
+//! swapped for a plain variable whose value is computed from the render data,
+//! so helper output is escaped like any other value.
+//!
+//! # Helpers
+//!
+//! - `{{number value}}`, `{{number value digits}}`, `{{number value "locale"}}`:
+//!   formats a number with thousands separators, optionally rounded to a number
+//!   of significant digits and using a locale's separators (`en`, `de`, `fr`...).
+//! - `{{currency value "EUR"}}`, `{{currency value "EUR" "locale"}}`: formats an
+//!   amount of money with the currency's symbol and decimals, placed and
+//!   separated by the locale's conventions, e.g. `€1,234.56` or `1.234,56 €`.
+//! - `{{list items}}`, `{{list items "or"}}`, `{{list items "and" "no-serial"}}`:
+//!   joins comma-separated items as `a, b, and c`.
+//! - `{{a word}}`: prefixes a word with `a` or `an`, e.g. `an apple`.
+//! - `{{possessive name}}`: `Jake's`, or `James'` for names ending in `s`.
+//! - `{{they pronouns}}`, `{{them pronouns}}`, `{{their pronouns}}`,
+//!   `{{theirs pronouns}}`, `{{themselves pronouns}}`: the matching form of
+//!   pronouns given as `he`, `she/her`, `they` or a full set like
+//!   `xe/xem/xyr/xyrs/xemself`.
+//! - `{{verb subject "is" "are"}}`: the singular or plural verb form, for a
+//!   count, a comma-separated list or pronouns.
+//! - `{{truncate value 20}}`: cuts a value to 20 visible characters, ending
+//!   with an ellipsis if anything was cut.
+//! - `{{date timestamp}}`, `{{date timestamp "%e %B %Y"}}` (feature `datetime`):
+//!   formats a timestamp in UTC.
+//! - `{{ago timestamp}}` (feature `datetime`): the time since or until a
+//!   timestamp, like `3 days ago` or `in 2 hours`.
+//! - `{{duration seconds}}` (feature `datetime`): a duration like `3 days`.
+//!
+//! `number` and `currency` use the [active locale](crate::HotText::set_locale())
+//! unless they are given one.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "datetime")]
+use crate::datetime;
+use crate::grammar;
+use crate::truncate;
+
+#[derive(Debug)]
+pub struct HelperError {
+    pub helper: String,
+    pub message: String,
+}
+
+impl fmt::Display for HelperError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HelperError in `{}`: {}", self.helper, self.message)
+    }
+}
+
+impl std::error::Error for HelperError {}
+
+type HelperFn = fn(&[String]) -> Result<String, String>;
+
+fn helper(name: &str) -> Option<HelperFn> {
+    Some(match name {
+        "number" => number,
+        "currency" => currency,
+        "list" => grammar::list,
+        "a" | "an" => grammar::article,
+        "possessive" => grammar::possessive,
+        "they" => |args| grammar::pronoun(0, args),
+        "them" => |args| grammar::pronoun(1, args),
+        "their" => |args| grammar::pronoun(2, args),
+        "theirs" => |args| grammar::pronoun(3, args),
+        "themselves" => |args| grammar::pronoun(4, args),
+        "verb" => grammar::verb,
+        "truncate" => truncate::truncate_value,
+        #[cfg(feature = "datetime")]
+        "date" => datetime::date,
+        #[cfg(feature = "datetime")]
+        "ago" => datetime::ago,
+        #[cfg(feature = "datetime")]
+        "duration" => datetime::duration,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Arg {
+    Var(String),
+    Literal(String),
+}
+
+#[derive(Debug, Clone)]
+struct HelperCall {
+    name: String,
+    args: Vec<Arg>,
+    variable: String,
+}
+
+/// A line with its helper tags swapped for variables.
+#[derive(Debug, Clone)]
+pub(crate) struct Prepared {
+    pub template: String,
+    calls: Vec<HelperCall>,
+}
+
+impl Prepared {
+    /// Computes the value of every helper variable from the render data.
+    pub fn values(&self, data: &HashMap<&str, &str>) -> Result<Vec<(String, String)>, HelperError> {
+        self.calls
+            .iter()
+            .map(|call| {
+                let error = |message: String| HelperError {
+                    helper: call.name.clone(),
+                    message,
+                };
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        Arg::Var(name) => data
+                            .get(name.as_str())
+                            .map(|value| value.to_string())
+                            .ok_or_else(|| error(format!("no value for `{}`", name))),
+                        Arg::Literal(value) => Ok(value.clone()),
+                    })
+                    .collect::<Result<Vec<String>, _>>()?;
+                let value = helper(&call.name).expect("helper exists")(&args).map_err(error)?;
+                Ok((call.variable.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// Splits a tag into words, keeping quoted strings together.
+fn split_tag(tag: &str) -> Option<Vec<Arg>> {
+    let mut args = Vec::new();
+    let mut chars = tag.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut literal = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => literal.push(chars.next()?),
+                    c => literal.push(c),
+                }
+            }
+            args.push(Arg::Literal(literal));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            if word.parse::<f64>().is_ok() {
+                args.push(Arg::Literal(word));
+            } else {
+                args.push(Arg::Var(word));
+            }
+        }
+    }
+    Some(args)
+}
+
+/// Swaps the helper tags in `line` for variables, or returns `None` if it has none.
+/// Locale-aware helpers default to `locale`, if it's one they know.
+pub(crate) fn prepare(line: &str, locale: Option<&str>) -> Option<Prepared> {
+    let locale = locale.filter(|locale| separators(locale).is_some());
+    let mut template = String::with_capacity(line.len());
+    let mut calls = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let (before, tag_start) = rest.split_at(start);
+        template.push_str(before);
+        let end = match tag_start.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &tag_start[2..end];
+        let call = match split_tag(tag).as_deref() {
+            Some([Arg::Var(name), args @ ..]) if !args.is_empty() && helper(name).is_some() => {
+                let mut args = args.to_vec();
+                // Given before any other options, so a locale in the tag wins.
+                let position = match name.as_str() {
+                    "number" => Some(1),
+                    "currency" if args.len() >= 2 => Some(2),
+                    _ => None,
+                };
+                if let (Some(position), Some(locale)) = (position, locale) {
+                    args.insert(position, Arg::Literal(locale.to_string()));
+                }
+                Some(HelperCall {
+                    name: name.clone(),
+                    args,
+                    variable: format!("__helper{}", calls.len()),
+                })
+            }
+            _ => None,
+        };
+        match call {
+            Some(call) => {
+                template.push_str("{{");
+                template.push_str(&call.variable);
+                template.push_str("}}");
+                calls.push(call);
+            }
+            None => template.push_str(&tag_start[..end + 2]),
+        }
+        rest = &tag_start[end + 2..];
+    }
+    template.push_str(rest);
+    if calls.is_empty() {
+        None
+    } else {
+        Some(Prepared { template, calls })
+    }
+}
+
+/// The names of the data a line reads, from plain tags, sections and
+/// helper arguments, sorted and without duplicates.
+pub(crate) fn variables(line: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let tag_start = &rest[start + 2..];
+        let end = match tag_start.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = tag_start[..end].trim_matches(['{', '}', '~']).trim();
+        rest = &tag_start[end + 2..];
+        let tag = match tag.chars().next() {
+            Some('!' | '>' | '/' | '=' | '$') | None => continue,
+            Some('#' | '^' | '&') => &tag[1..],
+            Some(_) => tag,
+        };
+        match split_tag(tag).as_deref() {
+            Some([Arg::Var(name), args @ ..]) if !args.is_empty() && helper(name).is_some() => {
+                names.extend(args.iter().filter_map(|arg| match arg {
+                    Arg::Var(name) => Some(name.clone()),
+                    Arg::Literal(_) => None,
+                }))
+            }
+            Some([Arg::Var(name)]) if name != "." => names.push(name.clone()),
+            _ => {}
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The thousands and decimal separators of a locale.
+fn separators(locale: &str) -> Option<(&'static str, &'static str)> {
+    let language = locale.split(['-', '_']).next()?.to_ascii_lowercase();
+    Some(match language.as_str() {
+        "en" | "ja" | "ko" | "zh" | "th" | "he" => (",", "."),
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "da" | "tr" | "el" => (".", ","),
+        "fr" | "ru" | "pl" | "sv" | "cs" | "fi" | "nb" | "uk" | "hu" | "sk" => ("\u{a0}", ","),
+        _ => return None,
+    })
+}
+
+/// Formats a number with thousands separators: `{{number value [digits|"locale"]...}}`.
+fn number(args: &[String]) -> Result<String, String> {
+    let (value, options) = args.split_first().ok_or("expected a number")?;
+    let mut digits = None;
+    let (mut thousands, mut decimal) = (",", ".");
+    for option in options {
+        match option.parse::<usize>() {
+            Ok(n) if n > 0 => digits = Some(n),
+            _ => {
+                let (t, d) =
+                    separators(option).ok_or_else(|| format!("unknown locale `{}`", option))?;
+                thousands = t;
+                decimal = d;
+            }
+        }
+    }
+
+    let value = value.trim();
+    let formatted = match digits {
+        Some(digits) => {
+            let number: f64 = value
+                .parse()
+                .map_err(|_| format!("`{}` is not a number", value))?;
+            round_significant(number, digits)
+        }
+        None => {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("`{}` is not a number", value))?;
+            value.trim_start_matches('+').to_string()
+        }
+    };
+    Ok(group(&formatted, thousands, decimal))
+}
+
+/// The symbol and number of decimals of a currency.
+fn currency_format(code: &str) -> (&str, usize) {
+    match code {
+        "USD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "KRW" => ("₩", 0),
+        "INR" => ("₹", 2),
+        "RUB" => ("₽", 2),
+        code => (code, 2),
+    }
+}
+
+/// Formats an amount of money: `{{currency value "CODE" ["locale"]...}}`.
+fn currency(args: &[String]) -> Result<String, String> {
+    let (value, code, options) = match args {
+        [value, code, options @ ..] => (value, code.to_ascii_uppercase(), options),
+        _ => return Err("expected an amount and a currency code".to_string()),
+    };
+    let mut locale = "en";
+    for option in options {
+        separators(option).ok_or_else(|| format!("unknown locale `{}`", option))?;
+        locale = option;
+    }
+    let (thousands, decimal) = separators(locale).expect("locale was checked");
+    let amount: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{}` is not a number", value.trim()))?;
+    let (symbol, decimals) = currency_format(&code);
+    let digits = group(
+        &format!("{:.*}", decimals, amount.abs()),
+        thousands,
+        decimal,
+    );
+    let sign = if amount < 0.0 { "-" } else { "" };
+    let symbol_first = decimal == ".";
+    Ok(match (symbol_first, symbol.chars().count() > 1) {
+        (true, false) => format!("{}{}{}", sign, symbol, digits),
+        (true, true) => format!("{}{}\u{a0}{}", sign, symbol, digits),
+        (false, _) => format!("{}{}\u{a0}{}", sign, digits, symbol),
+    })
+}
+
+/// Formats `number` rounded to `digits` significant digits.
+fn round_significant(number: f64, digits: usize) -> String {
+    if number == 0.0 || !number.is_finite() {
+        return number.to_string();
+    }
+    let magnitude = number.abs().log10().floor() as i32;
+    let decimals = digits as i32 - 1 - magnitude;
+    if decimals >= 0 {
+        format!("{:.*}", decimals as usize, number)
+    } else {
+        let scale = 10f64.powi(-decimals);
+        format!("{:.0}", (number / scale).round() * scale)
+    }
+}
+
+/// Inserts thousands separators into a plain decimal number.
+fn group(number: &str, thousands: &str, decimal: &str) -> String {
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", number),
+    };
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+    let mut out = String::from(sign);
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            out.push_str(thousands);
+        }
+        out.push(c);
+    }
+    if let Some(fraction) = fraction {
+        out.push_str(decimal);
+        out.push_str(fraction);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(args: &[&str]) -> Result<String, String> {
+        number(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn prepare_tags() {
+        let prepared = prepare("You earned {{number gold}} {{currency}}.", None).unwrap();
+        assert_eq!(prepared.template, "You earned {{__helper0}} {{currency}}.");
+
+        let data: HashMap<&str, &str> = vec![("gold", "1234567")].into_iter().collect();
+        assert_eq!(
+            prepared.values(&data).unwrap(),
+            vec![("__helper0".to_string(), "1,234,567".to_string())]
+        );
+        assert!(prepared.values(&HashMap::new()).is_err());
+        assert!(prepare("Hello, {{name}}! {{#items}}{{.}}{{/items}}", None).is_none());
+
+        let prepared = prepare("{{number gold}} {{number gold \"en\"}}", Some("de")).unwrap();
+        assert_eq!(
+            prepared.values(&data).unwrap()[..],
+            [
+                ("__helper0".to_string(), "1.234.567".to_string()),
+                ("__helper1".to_string(), "1,234,567".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn read_variables() {
+        assert_eq!(
+            variables("{{! note }}{{name}} earned {{number gold \"de\"}}, {{{name}}}{{~#items}}{{.}}{{/items}}"),
+            vec!["gold", "items", "name"]
+        );
+        assert!(variables("{{> shop.footer}} Hi!").is_empty());
+    }
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(call(&["1234567"]).unwrap(), "1,234,567");
+        assert_eq!(call(&["-1234.5"]).unwrap(), "-1,234.5");
+        assert_eq!(call(&["999"]).unwrap(), "999");
+        assert_eq!(call(&["1234567", "3"]).unwrap(), "1,230,000");
+        assert_eq!(call(&["3.14159", "3"]).unwrap(), "3.14");
+        assert_eq!(call(&["1234567.5", "de"]).unwrap(), "1.234.567,5");
+        assert!(call(&["lots"]).is_err());
+        assert!(call(&["1", "xx"]).is_err());
+    }
+
+    #[test]
+    fn format_currency() {
+        let call =
+            |args: &[&str]| currency(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+        assert_eq!(call(&["1234.5", "EUR"]).unwrap(), "€1,234.50");
+        assert_eq!(call(&["1234.5", "eur", "de"]).unwrap(), "1.234,50\u{a0}€");
+        assert_eq!(call(&["-1500", "JPY"]).unwrap(), "-¥1,500");
+        assert_eq!(call(&["12", "CHF"]).unwrap(), "CHF\u{a0}12.00");
+        assert!(call(&["12"]).is_err());
+        assert!(call(&["12", "USD", "xx"]).is_err());
+    }
+}
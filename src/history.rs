@@ -0,0 +1,122 @@
+//! Optional record of recent draws, for debugging and anti-repeat logic.
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// A line drawn from the collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draw {
+    /// The key the line was drawn from, after following fallbacks.
+    pub key: String,
+    pub line: String,
+}
+
+/// The last draws, globally and per key.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    capacity: usize,
+    draws: VecDeque<Draw>,
+    by_key: HashMap<String, VecDeque<String>>,
+}
+
+impl History {
+    fn new(capacity: usize) -> Self {
+        History {
+            capacity,
+            ..History::default()
+        }
+    }
+
+    fn record(&mut self, key: &str, line: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.draws.len() == self.capacity {
+            self.draws.pop_front();
+        }
+        self.draws.push_back(Draw {
+            key: key.to_string(),
+            line: line.to_string(),
+        });
+        let lines = self.by_key.entry(key.to_string()).or_default();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Starts recording the last `capacity` draws, both globally and for each
+    /// key, or stops recording with `None`.
+    ///
+    /// Changing the capacity clears the recorded draws.
+    pub fn set_history(&mut self, capacity: Option<usize>) {
+        self.history = capacity.map(History::new);
+    }
+
+    /// Chainable variant of [`HotText::set_history()`]
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.set_history(Some(capacity));
+        self
+    }
+
+    /// Gets the recorded draws, oldest first.
+    pub fn history(&self) -> Vec<&Draw> {
+        self.history
+            .iter()
+            .flat_map(|history| history.draws.iter())
+            .collect()
+    }
+
+    /// Gets the lines recently drawn from `key`, oldest first.
+    pub fn key_history(&self, key: &str) -> Vec<&str> {
+        self.history
+            .iter()
+            .filter_map(|history| history.by_key.get(key))
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Forgets the recorded draws, keeping the capacity.
+    pub fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            *history = History::new(history.capacity);
+        }
+    }
+
+    pub(crate) fn record_draw(&mut self, key: &str, line: &str) {
+        if let Some(history) = &mut self.history {
+            history.record(key, line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_draws() {
+        let mut ht = HotText::new(rand::thread_rng()).with_history(2);
+        ht.insert("greet", "Hi!").unwrap();
+        ht.insert("bye", "Bye!").unwrap();
+
+        ht.get_line_raw("greet");
+        ht.get_line_raw("bye");
+        ht.get_line_raw("greet");
+        ht.get_line_matching("by?");
+
+        let keys: Vec<&str> = ht.history().iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, vec!["greet", "bye"]);
+        assert_eq!(ht.key_history("greet"), vec!["Hi!", "Hi!"]);
+        assert_eq!(ht.key_history("bye"), vec!["Bye!", "Bye!"]);
+
+        ht.clear_history();
+        assert!(ht.history().is_empty());
+    }
+}
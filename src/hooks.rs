@@ -0,0 +1,119 @@
+//! Callbacks run whenever a line is drawn or rendered.
+
+use std::fmt;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{Draw, HotText};
+
+/// A rendered line, as passed to [`HotText::on_render()`] hooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderEvent<'a> {
+    /// The key the line was requested with.
+    pub key: &'a str,
+    /// The line before rendering.
+    pub line: &'a str,
+    /// The final output, after the [`Pipeline`](crate::Pipeline).
+    pub output: &'a str,
+}
+
+type DrawHook = Arc<dyn Fn(&Draw) + Send + Sync>;
+type RenderHook = Arc<dyn Fn(&RenderEvent) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    draw: Vec<DrawHook>,
+    render: Vec<RenderHook>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("draw", &self.draw.len())
+            .field("render", &self.render.len())
+            .finish()
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Adds a hook called with every line drawn, e.g. to count which lines a
+    /// player has heard.
+    pub fn on_draw<F: Fn(&Draw) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.hooks.draw.push(Arc::new(hook));
+    }
+
+    /// Adds a hook called with every rendered line and its final output, e.g.
+    /// for logging.
+    pub fn on_render<F: Fn(&RenderEvent) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.hooks.render.push(Arc::new(hook));
+    }
+
+    /// Removes every draw and render hook.
+    pub fn clear_hooks(&mut self) {
+        self.hooks = Hooks::default();
+    }
+
+    pub(crate) fn notify_draw(&self, key: &str, line: &str) {
+        if self.hooks.draw.is_empty() {
+            return;
+        }
+        let draw = Draw {
+            key: key.to_string(),
+            line: line.to_string(),
+        };
+        for hook in &self.hooks.draw {
+            hook(&draw);
+        }
+    }
+
+    #[cfg(feature = "templates")]
+    pub(crate) fn has_render_hooks(&self) -> bool {
+        !self.hooks.render.is_empty()
+    }
+
+    #[cfg(feature = "templates")]
+    pub(crate) fn notify_render(&self, key: &str, line: &str, output: &str) {
+        let event = RenderEvent { key, line, output };
+        for hook in &self.hooks.render {
+            hook(&event);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "templates"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn call_hooks() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("bear", "Grr, {{name}}.").unwrap();
+        ht.set_fallback("grizzly", "bear");
+
+        let draws = Arc::new(Mutex::new(Vec::new()));
+        let renders = Arc::new(Mutex::new(Vec::new()));
+        let d = Arc::clone(&draws);
+        ht.on_draw(move |draw| d.lock().unwrap().push(draw.clone()));
+        let r = Arc::clone(&renders);
+        ht.on_render(move |event| {
+            r.lock()
+                .unwrap()
+                .push(format!("{}: {} -> {}", event.key, event.line, event.output))
+        });
+
+        ht.get_line_raw("bear");
+        ht.render_line("grizzly", vec![("name", "Jake")]).unwrap();
+        assert_eq!(draws.lock().unwrap().len(), 2);
+        assert_eq!(draws.lock().unwrap()[1].key, "bear");
+        assert_eq!(
+            *renders.lock().unwrap(),
+            vec!["grizzly: Grr, {{name}}. -> Grr, Jake.".to_string()]
+        );
+
+        ht.clear_hooks();
+        ht.render_line("bear", vec![("name", "Jake")]).unwrap();
+        assert_eq!(draws.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,87 @@
+//! Stable IDs identifying which variant of a key was drawn.
+//!
+//! Lines may be given an authored `id` in their metadata, which survives edits
+//! to the text. Other lines get an ID derived from their key and text, which
+//! stays the same across runs and platforms until the text changes.
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::hash::fnv1a;
+use crate::HotText;
+
+/// Identifies one line of one key, e.g. for analytics, voice-over lookup or
+/// bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineId(String);
+
+impl LineId {
+    /// Derives the ID of a line without an authored one, as `key#hash`.
+    pub fn derive(key: &str, line: &str) -> Self {
+        LineId(format!("{}#{:016x}", key, fnv1a(line.as_bytes())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for LineId {
+    fn from(id: String) -> Self {
+        LineId(id)
+    }
+}
+
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets the ID of a line: its authored [`LineMeta::id`](crate::LineMeta::id)
+    /// from the base content or an enabled layer, or else [`LineId::derive()`].
+    pub fn line_id(&self, key: &str, line: &str) -> LineId {
+        std::iter::once(&*self.line_meta)
+            .chain(
+                self.layers
+                    .iter()
+                    .filter(|layer| layer.enabled)
+                    .map(|layer| &layer.line_meta),
+            )
+            .find_map(|line_meta| line_meta.get(key)?.get(line)?.id.clone())
+            .map_or_else(|| LineId::derive(key, line), LineId)
+    }
+
+    /// Gets one line of a key along with its [`LineId`], see
+    /// [`HotText::get_line_raw()`].
+    pub fn get_line_with_id(&mut self, key: &str) -> Option<(LineId, String)> {
+        let resolved = self.resolve_key(key);
+        let line = self.get_line_raw(key)?;
+        let id = self.line_id(resolved.as_deref().unwrap_or(key), &line);
+        Some((id, line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_ids() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi!").unwrap();
+        ht.set_fallback("hello", "greet");
+
+        let (id, line) = ht.get_line_with_id("hello").unwrap();
+        assert_eq!(line, "Hi!");
+        assert_eq!(id.as_str(), "greet#497c1519cdbf3661");
+        assert_eq!(id, LineId::derive("greet", "Hi!"));
+        assert_ne!(id, LineId::derive("greet", "Hi."));
+
+        ht.entry("bye").push_line("Bye!").unwrap().set_id("bye_01");
+        assert_eq!(ht.get_line_with_id("bye").unwrap().0.as_str(), "bye_01");
+        assert!(ht.get_line_with_id("missing").is_none());
+    }
+}
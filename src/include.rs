@@ -0,0 +1,200 @@
+//! `include` directives between content files.
+//!
+//! Any content file may list other files under the reserved `include` key,
+//! e.g. `include = ["common.toml", "names/*.toml"]`. Paths are resolved
+//! relative to the including file and may use `*` and `?` wildcards within a
+//! path component.
+//!
+//! Profile files (see [`ProfileMerge`](crate::ProfileMerge)) are looked up for
+//! every file loaded, including included ones.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use rand::Rng;
+
+use crate::format::{Format, LoadError};
+use crate::profile::profile_path;
+use crate::{HotText, LoadWarningKind, SCHEMA_VERSION};
+
+/// The reserved key listing files to include.
+pub const INCLUDE_KEY: &str = "include";
+
+#[derive(Debug)]
+pub struct IncludeError {
+    /// The chain of files leading to the error, outermost first.
+    pub chain: Vec<PathBuf>,
+    pub message: String,
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chain: Vec<String> = self.chain.iter().map(|p| p.display().to_string()).collect();
+        write!(f, "IncludeError ({}): {}", chain.join(" -> "), self.message)
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+impl<R: Rng> HotText<R> {
+    /// Loads a content file and, recursively, every file it includes.
+    pub(crate) fn load_file(&mut self, file: &Path, format: Format) -> Result<(), Box<dyn Error>> {
+        self.observe_changes(|ht| ht.load_file_included(file, format, &mut Vec::new(), false))
+    }
+
+    fn load_file_included(
+        &mut self,
+        file: &Path,
+        format: Format,
+        stack: &mut Vec<PathBuf>,
+        is_profile: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let canonical = fs::canonicalize(file)?;
+        if stack.contains(&canonical) {
+            let mut chain = stack.clone();
+            chain.push(canonical);
+            return Err(IncludeError {
+                chain,
+                message: "include cycle".to_string(),
+            }
+            .into());
+        }
+
+        let content = fs::read_to_string(file)?;
+        let mut content = format
+            .parse(&content, self.strict)
+            .map_err(|source| LoadError::new(file, &content, format, source))?;
+        let includes = content.line_pairs.remove(INCLUDE_KEY).unwrap_or_default();
+        self.max_lengths.extend(content.max_lengths.drain(..));
+
+        stack.push(canonical);
+        let base = file.parent().unwrap_or_else(|| Path::new(""));
+        let mut patterns: Vec<String> = includes.into_iter().collect();
+        patterns.sort();
+        for pattern in patterns {
+            for path in expand(base, &pattern)? {
+                let format = Format::from_path(&path).ok_or_else(|| IncludeError {
+                    chain: stack.clone(),
+                    message: format!("unsupported format for `{}`", path.display()),
+                })?;
+                self.load_file_included(&path, format, stack, false)?;
+            }
+        }
+
+        if is_profile {
+            let first_warning = self.warnings.len();
+            self.load_profile_hashmap(content.line_pairs)?;
+            self.load_meta(content.line_meta);
+            self.attribute_warnings(first_warning, file);
+        } else {
+            let first_warning = self.warnings.len();
+            if content.schema_version.is_none_or(|v| v < SCHEMA_VERSION) {
+                self.warn(LoadWarningKind::OutdatedSchema {
+                    version: content.schema_version,
+                    current: SCHEMA_VERSION,
+                });
+            }
+            for (key, line) in content.duplicates {
+                self.warn(LoadWarningKind::DuplicateLine { key, line });
+            }
+            self.load_pairs(content.line_pairs)?;
+            self.load_meta(content.line_meta);
+            self.attribute_warnings(first_warning, file);
+            if let Some((profile, _)) = &self.profile {
+                let profile_file = profile_path(file, profile);
+                if profile_file.is_file() {
+                    self.load_file_included(&profile_file, format, stack, true)?;
+                }
+            }
+        }
+        stack.pop();
+        Ok(())
+    }
+}
+
+/// Expands a possibly wildcarded path relative to `base`, sorted by path.
+fn expand(base: &Path, pattern: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths = vec![base.to_path_buf()];
+    for component in Path::new(pattern).components() {
+        let part = match component {
+            Component::Normal(part) => part.to_string_lossy(),
+            other => {
+                for path in paths.iter_mut() {
+                    path.push(other.as_os_str());
+                }
+                continue;
+            }
+        };
+        if !part.contains(['*', '?']) {
+            for path in paths.iter_mut() {
+                path.push(part.as_ref());
+            }
+            continue;
+        }
+        let mut matches = Vec::new();
+        for dir in &paths {
+            let entries = match fs::read_dir(if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries {
+                let name = entry?.file_name();
+                if wildcard_match(&part, &name.to_string_lossy()) {
+                    matches.push(dir.join(name));
+                }
+            }
+        }
+        paths = matches;
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Matches `name` against a pattern where `*` is any run and `?` is any character.
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    p = bp + 1;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("*.toml", "names.toml"));
+        assert!(wildcard_match("n?mes*", "names.toml"));
+        assert!(wildcard_match("*a*b*", "xxaxxbxx"));
+        assert!(!wildcard_match("*.toml", "names.json"));
+        assert!(!wildcard_match("a?", "a"));
+    }
+}
@@ -0,0 +1,212 @@
+//! JSON5 (and JSONC) content files.
+//!
+//! ```json5
+//! {
+//!     // Comments, unquoted keys and trailing commas are fine.
+//!     meta: ['Welcome!'],
+//!     "combat.encounter": [
+//!         "You encounter a lion!",
+//!         'Oh no! It\'s a "bear"!',
+//!     ],
+//! }
+//! ```
+//!
+//! JSON5 is translated into strict JSON before being deserialized, preserving
+//! line breaks so that error positions still point into the original file.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+#[derive(Debug)]
+pub struct Json5ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Json5ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Json5ParseError on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Json5ParseError {}
+
+/// Deserializes JSON5 content.
+pub(crate) fn parse<T: DeserializeOwned>(content: &str) -> Result<T, Box<dyn Error>> {
+    Ok(serde_json::from_str(&to_json(content)?)?)
+}
+
+/// Translates JSON5 into strict JSON.
+fn to_json(content: &str) -> Result<String, Json5ParseError> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut line = 1;
+    let mut i = 0;
+
+    let error = |line: usize, message: &str| Json5ParseError {
+        line,
+        message: message.to_string(),
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some(end) = comment_end(&chars, i) {
+            if end > chars.len() {
+                return Err(error(line, "unterminated block comment"));
+            }
+            let newlines = chars[i..end].iter().filter(|&&c| c == '\n').count();
+            out.push_str(&"\n".repeat(newlines));
+            line += newlines;
+            i = end;
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                let start = line;
+                out.push('"');
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some(&q) if q == c => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some(e @ ('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't')) => {
+                                    out.push('\\');
+                                    out.push(*e);
+                                }
+                                Some('u') => out.push_str("\\u"),
+                                Some('\'') => out.push('\''),
+                                Some('v') => out.push_str("\\u000b"),
+                                Some('0') => out.push_str("\\u0000"),
+                                Some('x') => {
+                                    let hex: String = chars[i + 1..].iter().take(2).collect();
+                                    out.push_str("\\u00");
+                                    out.push_str(&hex);
+                                    i += 2;
+                                }
+                                Some('\r') if chars.get(i + 1) == Some(&'\n') => {
+                                    i += 1;
+                                    line += 1;
+                                }
+                                Some('\n') => line += 1,
+                                Some(&e) => out.push(e),
+                                None => return Err(error(start, "unterminated string")),
+                            }
+                        }
+                        Some('"') => out.push_str("\\\""),
+                        Some('\n') | None => return Err(error(start, "unterminated string")),
+                        Some(&c) if c.is_control() => {
+                            out.push_str(&format!("\\u{:04x}", c as u32));
+                        }
+                        Some(&c) => out.push(c),
+                    }
+                    i += 1;
+                }
+                out.push('"');
+                out.push_str(&"\n".repeat(line - start));
+            }
+            ',' => {
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        Some(c) if c.is_whitespace() => j += 1,
+                        Some(_) => match comment_end(&chars, j) {
+                            Some(end) => j = end,
+                            None => break,
+                        },
+                        None => break,
+                    }
+                }
+                if !matches!(chars.get(j), Some(']') | Some('}')) {
+                    out.push(',');
+                }
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '$')
+                    .count();
+                let ident: String = chars[i..i + len].iter().collect();
+                let is_key = chars[i + len..]
+                    .iter()
+                    .find(|c| !c.is_whitespace())
+                    .is_some_and(|&c| c == ':');
+                if is_key {
+                    out.push_str(&format!("\"{}\"", ident));
+                } else {
+                    out.push_str(&ident);
+                }
+                i += len;
+                continue;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                out.push(c);
+            }
+        }
+        i += 1;
+    }
+
+    Ok(out)
+}
+
+/// If a comment starts at `i`, returns the index just past its end. An
+/// unterminated block comment returns an index past the end of `chars`.
+fn comment_end(chars: &[char], i: usize) -> Option<usize> {
+    if chars.get(i) != Some(&'/') {
+        return None;
+    }
+    match chars.get(i + 1) {
+        Some('/') => Some(
+            chars[i..]
+                .iter()
+                .position(|&c| c == '\n')
+                .map_or(chars.len(), |p| i + p),
+        ),
+        Some('*') => Some(
+            chars[i + 2..]
+                .windows(2)
+                .position(|w| w == ['*', '/'])
+                .map_or(chars.len() + 1, |p| i + 2 + p + 2),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinePairs;
+
+    #[test]
+    fn parse_json5() {
+        let line_pairs: LinePairs = parse(
+            "/* Dungeon lines */\n\
+             {\n\
+             \x20   meta: ['Welcome!',], // Greetings\n\
+             \x20   \"combat.encounter\": [\n\
+             \x20       \"You encounter a lion!\",\n\
+             \x20       'Oh no! It\\'s a \"bear\"!',\n\
+             \x20       'A long \\\n\
+             line.\\x21',\n\
+             \x20   ],\n\
+             }\n",
+        )
+        .unwrap();
+
+        assert!(line_pairs["meta"].contains("Welcome!"));
+        assert!(line_pairs["combat.encounter"].contains("Oh no! It's a \"bear\"!"));
+        assert!(line_pairs["combat.encounter"].contains("A long line.!"));
+    }
+
+    #[test]
+    fn parse_json5_errors() {
+        assert_eq!(to_json("{\n/* open").unwrap_err().line, 2);
+        assert_eq!(to_json("{\n\n'open\n}").unwrap_err().line, 3);
+    }
+}
@@ -0,0 +1,193 @@
+//! Named content layers that can be switched on and off at runtime.
+//!
+//! A layer holds content loaded apart from the base collection, such as
+//! seasonal lines. While a layer is enabled its lines are drawn alongside the
+//! base lines of the same key; disabling it takes effect on the next draw.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::mem;
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::{HotText, LineMetas, LinePairs};
+
+/// Content loaded into a named layer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layer {
+    pub name: String,
+    pub enabled: bool,
+    pub line_pairs: LinePairs,
+    pub line_meta: LineMetas,
+}
+
+/// Collects the lines of `key` from the base content and every enabled layer,
+/// along with their weights.
+pub(crate) fn candidates<'a>(
+    line_pairs: &'a LinePairs,
+    line_meta: &'a LineMetas,
+    layers: &'a [Layer],
+    key: &str,
+) -> Vec<(&'a String, f64)> {
+    let mut candidates: Vec<(&String, f64)> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let sources = std::iter::once((line_pairs, line_meta)).chain(
+        layers
+            .iter()
+            .filter(|layer| layer.enabled)
+            .map(|layer| (&layer.line_pairs, &layer.line_meta)),
+    );
+    for (line_pairs, line_meta) in sources {
+        let meta = line_meta.get(key);
+        for line in line_pairs.get(key).into_iter().flatten() {
+            if seen.insert(line) {
+                let weight = meta.and_then(|m| m.get(line)).map_or(1.0, |m| m.weight);
+                candidates.push((line, weight));
+            }
+        }
+    }
+    // Sorted so a seeded RNG draws the same line regardless of hash order.
+    candidates.sort_unstable_by_key(|(line, _)| *line);
+    candidates
+}
+
+/// Whether the line is tagged `tag` in the base content or any enabled layer.
+pub(crate) fn has_tag(
+    line_meta: &LineMetas,
+    layers: &[Layer],
+    key: &str,
+    line: &str,
+    tag: &str,
+) -> bool {
+    std::iter::once(line_meta)
+        .chain(
+            layers
+                .iter()
+                .filter(|layer| layer.enabled)
+                .map(|layer| &layer.line_meta),
+        )
+        .filter_map(|line_meta| line_meta.get(key)?.get(line))
+        .any(|meta| meta.tags.contains(tag))
+}
+
+impl<R: Rng> HotText<R> {
+    /// Loads a file into the named layer, creating it (enabled) if needed.
+    ///
+    /// Layer content is kept apart from the base collection, so it can be
+    /// switched on and off with [`HotText::set_layer_enabled()`].
+    pub fn load_layer<P: AsRef<Path>>(
+        &mut self,
+        name: &str,
+        file: P,
+    ) -> Result<(), Box<dyn Error>> {
+        self.observe_changes(|ht| {
+            let index = match ht.layers.iter().position(|layer| layer.name == name) {
+                Some(index) => index,
+                None => {
+                    ht.layers.push(Layer {
+                        name: name.to_string(),
+                        enabled: true,
+                        ..Layer::default()
+                    });
+                    ht.layers.len() - 1
+                }
+            };
+
+            let layer = &mut ht.layers[index];
+            let base_pairs =
+                mem::replace(&mut ht.line_pairs, mem::take(&mut layer.line_pairs).into());
+            let base_meta = mem::replace(&mut ht.line_meta, mem::take(&mut layer.line_meta).into());
+            let result = ht.load(file);
+            let (pairs, meta) = (
+                mem::take(&mut *ht.line_pairs),
+                mem::take(&mut *ht.line_meta),
+            );
+            let layer = &mut ht.layers[index];
+            layer.line_pairs = pairs;
+            layer.line_meta = meta;
+            ht.line_pairs = base_pairs;
+            ht.line_meta = base_meta;
+            result
+        })
+    }
+
+    /// Chainable variant of [`HotText::load_layer()`]
+    pub fn with_load_layer<P: AsRef<Path>>(
+        mut self,
+        name: &str,
+        file: P,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.load_layer(name, file)?;
+        Ok(self)
+    }
+
+    /// Switches a layer on or off. Returns `false` if there is no such layer.
+    pub fn set_layer_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.observe_changes(
+            |ht| match ht.layers.iter_mut().find(|layer| layer.name == name) {
+                Some(layer) => {
+                    layer.enabled = enabled;
+                    true
+                }
+                None => false,
+            },
+        )
+    }
+
+    /// Whether a layer is enabled, or `None` if there is no such layer.
+    pub fn layer_enabled(&self, name: &str) -> Option<bool> {
+        self.layers
+            .iter()
+            .find(|layer| layer.name == name)
+            .map(|layer| layer.enabled)
+    }
+
+    /// Gets the keys of the base content and every enabled layer, sorted.
+    pub(crate) fn drawable_keys(&self) -> Vec<&String> {
+        let mut keys: Vec<&String> = self
+            .line_pairs
+            .keys()
+            .chain(
+                self.layers
+                    .iter()
+                    .filter(|layer| layer.enabled)
+                    .flat_map(|layer| layer.line_pairs.keys()),
+            )
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Gets the names of all layers, in the order they were created.
+    pub fn layers(&self) -> Vec<&str> {
+        self.layers
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_layer() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_load("./test_lines.toml")
+            .unwrap()
+            .with_load_layer("holiday", "./test_includes/common.json")
+            .unwrap();
+
+        assert_eq!(ht.layers(), vec!["holiday"]);
+        assert_eq!(ht.get_line_raw("names").unwrap(), "Aldric");
+        assert!(ht.get_lines_raw("meta.welcome").is_some());
+
+        assert!(ht.set_layer_enabled("holiday", false));
+        assert_eq!(ht.layer_enabled("holiday"), Some(false));
+        assert!(ht.get_line_raw("names").is_none());
+        assert!(!ht.set_layer_enabled("missing", true));
+    }
+}
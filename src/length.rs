@@ -0,0 +1,211 @@
+//! Limits on how long lines may be once rendered, for UI slots with hard
+//! character limits.
+//!
+//! Limits are set per key or per namespace with `*` and `?` wildcards, e.g.
+//! `ui.button.*`, in code or in content files under the reserved
+//! [`MAX_LENGTH_KEY`]:
+//!
+//! ```toml
+//! schema_version = 2
+//! max_length = { "ui.button.*" = 12, "ui.tooltip" = 80 }
+//! ```
+//!
+//! Plain lines over their limit raise a [`LoadWarning`](crate::LoadWarning)
+//! as they are loaded. Templates depend on their data, so they are checked
+//! with sample data by [`HotText::check_lengths()`].
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{include, truncate, HotText, LoadWarningKind};
+#[cfg(feature = "templates")]
+use crate::{is_plain, Compiled};
+
+/// The reserved key mapping keys or namespaces to their maximum length.
+pub const MAX_LENGTH_KEY: &str = "max_length";
+
+/// A line rendering longer than the limit of its key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthViolation {
+    pub key: String,
+    pub line: String,
+    pub rendered: String,
+    /// The length of the rendered line, in visible characters.
+    pub length: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for LengthViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` line {:?} renders {} characters, over the limit of {}",
+            self.key, self.line, self.length, self.max
+        )
+    }
+}
+
+/// The length of `text` in visible characters, so accents and emoji count once.
+fn visible_length(text: &str) -> usize {
+    truncate::grapheme_starts(text).len()
+}
+
+impl<R: Rng> HotText<R> {
+    /// Limits the lines of keys matching `pattern` to `max` visible characters
+    /// once rendered, or removes the limit with `None`.
+    pub fn set_max_length(&mut self, pattern: &str, max: Option<usize>) {
+        match max {
+            Some(max) => self.max_lengths.insert(pattern.to_string(), max),
+            None => self.max_lengths.remove(pattern),
+        };
+    }
+
+    /// Chainable variant of [`HotText::set_max_length()`]
+    pub fn with_max_length(mut self, pattern: &str, max: usize) -> Self {
+        self.set_max_length(pattern, Some(max));
+        self
+    }
+
+    /// Gets the maximum length of the lines of `key`: the smallest limit of
+    /// any pattern matching it.
+    pub fn max_length(&self, key: &str) -> Option<usize> {
+        self.max_lengths
+            .iter()
+            .filter(|(pattern, _)| include::wildcard_match(pattern, key))
+            .map(|(_, max)| *max)
+            .min()
+    }
+
+    /// Renders every line of every key with a maximum length with the sample
+    /// `data`, and gets the lines that render too long, sorted by key and
+    /// line. Lines that fail to render are left to
+    /// [`HotText::render_report()`](crate::HotText::render_report) to find.
+    pub fn check_lengths(&self, data: &[(&str, &str)]) -> Vec<LengthViolation> {
+        #[cfg(feature = "templates")]
+        let data: std::collections::HashMap<&str, &str> = data.iter().copied().collect();
+        #[cfg(not(feature = "templates"))]
+        let _ = data;
+        let mut violations = Vec::new();
+        for key in self.drawable_keys() {
+            let max = match self.max_length(key) {
+                Some(max) => max,
+                None => continue,
+            };
+            let mut lines: Vec<&String> = std::iter::once(&*self.line_pairs)
+                .chain(
+                    self.layers
+                        .iter()
+                        .filter(|layer| layer.enabled)
+                        .map(|layer| &layer.line_pairs),
+                )
+                .filter_map(|line_pairs| line_pairs.get(key))
+                .flatten()
+                .collect();
+            lines.sort();
+            lines.dedup();
+            for line in lines {
+                #[cfg(feature = "templates")]
+                let rendered = if is_plain(line) {
+                    Ok(line.clone())
+                } else {
+                    Compiled::new(line, self.capitalization, &self.locale)
+                        .and_then(|template| template.render_to_string(&data))
+                };
+                #[cfg(not(feature = "templates"))]
+                let rendered: Result<String, Box<dyn std::error::Error>> = Ok(line.clone());
+                let rendered = match rendered.and_then(|r| Ok(self.pipeline.apply(r)?)) {
+                    Ok(rendered) => rendered,
+                    Err(_) => continue,
+                };
+                let length = visible_length(&rendered);
+                if length > max {
+                    violations.push(LengthViolation {
+                        key: key.clone(),
+                        line: line.clone(),
+                        rendered,
+                        length,
+                        max,
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Records a warning if `line` is plain and longer than the limit of `key`.
+    pub(crate) fn check_length(&mut self, key: &str, line: &str) {
+        #[cfg(feature = "templates")]
+        if !is_plain(line) {
+            return;
+        }
+        let max = match self.max_length(key) {
+            Some(max) => max,
+            None => return,
+        };
+        let length = visible_length(line);
+        if length > max {
+            self.warn(LoadWarningKind::TooLong {
+                key: key.to_string(),
+                line: line.to_string(),
+                length,
+                max,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_lengths() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_max_length("ui.button.*", 8)
+            .with_max_length("ui.button.ok", 2);
+        ht.load_hashmap(
+            vec![(
+                "ui.button.cancel".to_string(),
+                vec!["Cancel".to_string(), "Never mind".to_string()]
+                    .into_iter()
+                    .collect(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            ht.take_warnings(),
+            vec![crate::LoadWarning {
+                file: None,
+                kind: LoadWarningKind::TooLong {
+                    key: "ui.button.cancel".to_string(),
+                    line: "Never mind".to_string(),
+                    length: 10,
+                    max: 8,
+                },
+            }]
+        );
+        assert_eq!(ht.max_length("ui.button.ok"), Some(2));
+        assert_eq!(ht.max_length("greet"), None);
+
+        let violations = ht.check_lengths(&[]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rendered, "Never mind");
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn check_rendered_lengths() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.load("./test_lengths.toml").unwrap();
+        assert_eq!(ht.max_length("ui.toast.loot"), Some(24));
+
+        let violations = ht.check_lengths(&[("item", "Sword of Destiny")]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rendered, "You found Sword of Destiny!");
+        assert_eq!((violations[0].length, violations[0].max), (27, 24));
+        assert!(ht.check_lengths(&[("item", "Axe")]).is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! The active locale of locale-aware helpers, and laying out values in
+//! right-to-left locales.
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// First strong isolate: lays out the text up to [`POP_ISOLATE`] in the
+/// direction of its first strong character, apart from the text around it.
+const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+const POP_ISOLATE: char = '\u{2069}';
+
+/// How lines are laid out while a right-to-left locale is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BidiOptions {
+    /// Wraps substituted values in Unicode directional isolates, so a
+    /// left-to-right item name doesn't scramble the Arabic or Hebrew line
+    /// around it. Defaults to `true`.
+    pub isolate: bool,
+    /// Swaps opening and closing brackets in the line's own text, for
+    /// renderers that don't mirror them. Defaults to `false`.
+    pub mirror_brackets: bool,
+}
+
+impl Default for BidiOptions {
+    fn default() -> Self {
+        BidiOptions {
+            isolate: true,
+            mirror_brackets: false,
+        }
+    }
+}
+
+/// Whether a locale's script is written right to left.
+pub(crate) fn is_rtl(locale: &str) -> bool {
+    let language = locale.split(['-', '_']).next().unwrap_or("");
+    matches!(
+        language.to_ascii_lowercase().as_str(),
+        "ar" | "he" | "fa" | "ur" | "yi" | "ps" | "dv" | "ckb" | "sd" | "ug"
+    )
+}
+
+pub(crate) fn isolate(value: &str) -> String {
+    format!("{}{}{}", FIRST_STRONG_ISOLATE, value, POP_ISOLATE)
+}
+
+/// Swaps `(`, `[` and `{` with their closing brackets outside of template tags.
+pub(crate) fn mirror_brackets(line: &str) -> String {
+    let mirror = |text: &str| -> String {
+        text.chars()
+            .map(|c| match c {
+                '(' => ')',
+                ')' => '(',
+                '[' => ']',
+                ']' => '[',
+                '{' => '}',
+                '}' => '{',
+                c => c,
+            })
+            .collect()
+    };
+    let mut mirrored = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end + 2,
+            None => break,
+        };
+        // Triple mustaches end with one more brace.
+        let end = if rest[start..].starts_with("{{{") && rest[end..].starts_with('}') {
+            end + 1
+        } else {
+            end
+        };
+        mirrored.push_str(&mirror(&rest[..start]));
+        mirrored.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    mirrored.push_str(&mirror(rest));
+    mirrored
+}
+
+/// The active locale and how it lays out lines.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Localization {
+    pub locale: Option<String>,
+    pub bidi: BidiOptions,
+}
+
+impl Localization {
+    /// The bidi options, if the active locale is right to left.
+    pub fn bidi(&self) -> Option<BidiOptions> {
+        self.locale
+            .as_deref()
+            .filter(|locale| is_rtl(locale))
+            .map(|_| self.bidi)
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the locale, like `de` or `pt-BR`, that the `number` and `currency`
+    /// helpers format with unless given one, so translated lines don't need
+    /// the locale repeated in every tag. Locales the helpers don't know are
+    /// formatted as English.
+    ///
+    /// Right-to-left locales like `ar` and `he` also lay lines out according
+    /// to the [`BidiOptions`].
+    pub fn set_locale(&mut self, locale: Option<&str>) {
+        self.locale.locale = locale.map(str::to_string);
+        if let Some(memo) = &mut self.render_memo {
+            memo.clear();
+        }
+    }
+
+    /// Chainable variant of [`HotText::set_locale()`]
+    pub fn with_locale(mut self, locale: Option<&str>) -> Self {
+        self.set_locale(locale);
+        self
+    }
+
+    /// Gets the active locale, if any.
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.locale.as_deref()
+    }
+
+    /// Sets how lines are laid out while a right-to-left locale is active.
+    pub fn set_bidi(&mut self, bidi: BidiOptions) {
+        self.locale.bidi = bidi;
+        if let Some(memo) = &mut self.render_memo {
+            memo.clear();
+        }
+    }
+
+    /// Chainable variant of [`HotText::set_bidi()`]
+    pub fn with_bidi(mut self, bidi: BidiOptions) -> Self {
+        self.set_bidi(bidi);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_for_locale() {
+        let mut ht = HotText::new(rand::thread_rng()).with_locale(Some("de-DE"));
+        ht.insert(
+            "shop.price",
+            "{{number count}} Stück für {{currency price \"EUR\"}}",
+        )
+        .unwrap();
+        let data = vec![("count", "1200"), ("price", "1234.56")];
+        assert_eq!(
+            ht.render_line("shop.price", data.clone()).unwrap(),
+            "1.200 Stück für 1.234,56\u{a0}€"
+        );
+
+        ht.set_locale(None);
+        assert_eq!(ht.locale(), None);
+        assert_eq!(
+            ht.render_line("shop.price", data).unwrap(),
+            "1,200 Stück für €1,234.56"
+        );
+    }
+
+    #[test]
+    fn lay_out_rtl() {
+        let mut ht = HotText::new(rand::thread_rng()).with_locale(Some("ar"));
+        ht.insert("loot", "وجدت {{item}} (نادر)").unwrap();
+        assert_eq!(
+            ht.render_line("loot", vec![("item", "Iron Sword")])
+                .unwrap(),
+            "وجدت \u{2068}Iron Sword\u{2069} (نادر)"
+        );
+
+        ht.set_bidi(BidiOptions {
+            isolate: false,
+            mirror_brackets: true,
+        });
+        assert_eq!(
+            ht.render_line("loot", vec![("item", "Iron Sword")])
+                .unwrap(),
+            "وجدت Iron Sword )نادر("
+        );
+        assert_eq!(
+            mirror_brackets("[{{{a}}}] {{#b}}(x){{/b}}"),
+            "]{{{a}}}[ {{#b}})x({{/b}}"
+        );
+
+        ht.set_locale(Some("en"));
+        ht.set_bidi(BidiOptions::default());
+        assert_eq!(
+            ht.render_line("loot", vec![("item", "Iron Sword")])
+                .unwrap(),
+            "وجدت Iron Sword (نادر)"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Exporting the list of keys for tools and frontends written in other
+//! languages.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+
+#[cfg(feature = "templates")]
+use crate::helpers;
+use crate::HotText;
+
+/// The languages [`HotText::export_manifest()`] writes key manifests in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ManifestFormat {
+    /// A JSON array of keys, or an object of keys to their placeholders.
+    #[default]
+    Json,
+    /// A `HotTextKey` string union type, and a `HotTextData` interface of
+    /// each key's placeholders.
+    TypeScript,
+    /// A C header with a `HOTTEXT_KEY_…` string constant for each key.
+    CHeader,
+}
+
+/// The name of a C constant for `key`, e.g. `HOTTEXT_KEY_COMBAT_SLAY`.
+fn c_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect();
+    format!("HOTTEXT_KEY_{}", name)
+}
+
+impl<R: Rng> HotText<R> {
+    /// The data each line of `key` reads, across the base content and
+    /// enabled layers.
+    #[cfg(feature = "templates")]
+    fn placeholders(&self, key: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .line_pairs
+            .get(key)
+            .into_iter()
+            .chain(
+                self.layers
+                    .iter()
+                    .filter(|layer| layer.enabled)
+                    .filter_map(|layer| layer.line_pairs.get(key)),
+            )
+            .flatten()
+            .flat_map(|line| helpers::variables(line))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    #[cfg(not(feature = "templates"))]
+    fn placeholders(&self, _key: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Exports every key, sorted, so web frontends and tools written in
+    /// other languages can check the keys they use against the content.
+    ///
+    /// With `placeholders`, the names of the data each key's lines read, like
+    /// `weapon` in `Your {{weapon}} breaks.`, are exported too.
+    pub fn export_manifest(&self, format: ManifestFormat, placeholders: bool) -> String {
+        let keys: Vec<(&String, Vec<String>)> = self
+            .drawable_keys()
+            .into_iter()
+            .map(|key| {
+                let names = if placeholders {
+                    self.placeholders(key)
+                } else {
+                    Vec::new()
+                };
+                (key, names)
+            })
+            .collect();
+        let quote = |text: &str| serde_json::Value::from(text).to_string();
+        match format {
+            ManifestFormat::Json if placeholders => {
+                let manifest: serde_json::Map<String, serde_json::Value> = keys
+                    .into_iter()
+                    .map(|(key, names)| (key.clone(), names.into()))
+                    .collect();
+                serde_json::to_string_pretty(&manifest).expect("manifest is valid JSON")
+            }
+            ManifestFormat::Json => {
+                let keys: Vec<&String> = keys.into_iter().map(|(key, _)| key).collect();
+                serde_json::to_string_pretty(&keys).expect("manifest is valid JSON")
+            }
+            ManifestFormat::TypeScript => {
+                let mut out = String::from("// Generated by hottext.\n\nexport type HotTextKey =");
+                if keys.is_empty() {
+                    out.push_str(" never");
+                }
+                for (key, _) in &keys {
+                    out.push_str(&format!("\n  | {}", quote(key)));
+                }
+                out.push_str(";\n");
+                if placeholders {
+                    out.push_str("\nexport interface HotTextData {\n");
+                    for (key, names) in &keys {
+                        let fields: Vec<String> = names
+                            .iter()
+                            .map(|name| format!(" {}: string;", quote(name)))
+                            .collect();
+                        out.push_str(&format!("  {}: {{{} }};\n", quote(key), fields.concat()));
+                    }
+                    out.push_str("}\n");
+                }
+                out
+            }
+            ManifestFormat::CHeader => {
+                let mut out = String::from(
+                    "/* Generated by hottext. */\n\n#ifndef HOTTEXT_KEYS_H\n#define HOTTEXT_KEYS_H\n\n",
+                );
+                for (key, names) in &keys {
+                    if !names.is_empty() {
+                        out.push_str(&format!("/* placeholders: {} */\n", names.join(", ")));
+                    }
+                    out.push_str(&format!("#define {} {}\n", c_name(key), quote(key)));
+                }
+                out.push_str(&format!(
+                    "\n#define HOTTEXT_KEY_COUNT {}\n\n#endif /* HOTTEXT_KEYS_H */\n",
+                    keys.len()
+                ));
+                out
+            }
+        }
+    }
+
+    /// Writes [`HotText::export_manifest()`] to `file`.
+    pub fn save_manifest<P: AsRef<Path>>(
+        &self,
+        file: P,
+        format: ManifestFormat,
+        placeholders: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        fs::write(file, self.export_manifest(format, placeholders))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_manifests() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("combat.slay", "Your {{weapon}} ends {{enemy}}.")
+            .unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+
+        assert_eq!(
+            ht.export_manifest(ManifestFormat::Json, false),
+            "[\n  \"combat.slay\",\n  \"greet\"\n]"
+        );
+        let typescript = ht.export_manifest(ManifestFormat::TypeScript, false);
+        assert!(
+            typescript.contains("export type HotTextKey =\n  | \"combat.slay\"\n  | \"greet\";")
+        );
+        let header = ht.export_manifest(ManifestFormat::CHeader, false);
+        assert!(header.contains("#define HOTTEXT_KEY_COMBAT_SLAY \"combat.slay\"\n"));
+        assert!(header.contains("#define HOTTEXT_KEY_COUNT 2\n"));
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn export_placeholders() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("combat.slay", "Your {{weapon}} ends {{enemy}}.")
+            .unwrap();
+        ht.insert("combat.slay", "{{enemy}} falls.").unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&ht.export_manifest(ManifestFormat::Json, true)).unwrap();
+        assert_eq!(json["combat.slay"], serde_json::json!(["enemy", "weapon"]));
+        let typescript = ht.export_manifest(ManifestFormat::TypeScript, true);
+        assert!(
+            typescript.contains("  \"combat.slay\": { \"enemy\": string; \"weapon\": string; };\n")
+        );
+        assert!(typescript.contains("  \"greet\": { };\n"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Markdown content, with headings as keys.
+//!
+//! ```markdown
+//! ## combat.encounter
+//!
+//! - You encounter a lion!
+//! - You stumble across a tiger!
+//!
+//! Oh no! It's a bear!
+//! ```
+//!
+//! Every list item and every paragraph under a heading is one variant of that
+//! heading's key. Anything before the first heading is ignored, as are fenced
+//! code blocks and headings with nothing beneath them.
+
+use crate::schema::Duplicates;
+use crate::LinePairs;
+
+/// Parses Markdown content into key/line pairs, recording lines repeated
+/// within a key in `duplicates`.
+pub(crate) fn parse(content: &str, duplicates: &mut Duplicates) -> LinePairs {
+    let mut line_pairs = LinePairs::default();
+    let mut key: Option<String> = None;
+    let mut block: Option<String> = None;
+    let mut in_fence = false;
+
+    let mut flush = |key: &Option<String>, block: &mut Option<String>| {
+        if let (Some(key), Some(line)) = (key, block.take()) {
+            if !line_pairs
+                .entry(key.clone())
+                .or_default()
+                .insert(line.clone())
+            {
+                duplicates.push((key.clone(), line));
+            }
+        }
+    };
+
+    for raw in content.lines() {
+        let trimmed = raw.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush(&key, &mut block);
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some(heading) = heading(trimmed) {
+            flush(&key, &mut block);
+            key = Some(heading.to_string());
+        } else if trimmed.is_empty() {
+            flush(&key, &mut block);
+        } else if let Some(item) = list_item(trimmed) {
+            flush(&key, &mut block);
+            block = Some(item.to_string());
+        } else if let Some(line) = block.as_mut() {
+            line.push(' ');
+            line.push_str(trimmed);
+        } else {
+            block = Some(trimmed.to_string());
+        }
+    }
+    flush(&key, &mut block);
+
+    line_pairs
+}
+
+/// Returns the text of an ATX heading (`## key`), without trailing `#`s.
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Returns the text of a bullet (`- `, `* `, `+ `) or numbered (`1. `) list item.
+fn list_item(line: &str) -> Option<&str> {
+    for bullet in &["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(item.trim());
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(item) = line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))
+        {
+            return Some(item.trim());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_markdown() {
+        let line_pairs = parse(
+            "# Dungeon lines\n\n## combat.encounter\n\n- You encounter a lion!\n* You stumble across\n  a tiger!\n1. Oh no! It's a bear!\n\nOh my,\nit's a dragon!\n\n## meta.welcome ##\n```\nignored\n```\nWelcome!\n",
+            &mut Vec::new(),
+        );
+
+        assert_eq!(line_pairs.len(), 2);
+        assert_eq!(line_pairs["combat.encounter"].len(), 4);
+        assert!(line_pairs["combat.encounter"].contains("You stumble across a tiger!"));
+        assert!(line_pairs["combat.encounter"].contains("Oh my, it's a dragon!"));
+        assert!(line_pairs["meta.welcome"].contains("Welcome!"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Markov-chain generation of novel lines from the lines of a key.
+
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::HotText;
+
+/// What a [`MarkovModel`] treats as one step of the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkovUnit {
+    /// Whole words, for things like book titles.
+    Words,
+    /// Single characters, for gibberish and made-up words.
+    Chars,
+}
+
+/// A Markov chain trained on a set of lines.
+#[derive(Debug, Clone)]
+pub struct MarkovModel {
+    unit: MarkovUnit,
+    order: usize,
+    /// The tokens seen after each run of `order` tokens. `None` ends the line.
+    transitions: HashMap<Vec<String>, Vec<Option<String>>>,
+}
+
+impl MarkovModel {
+    /// Trains a model where each token depends on the `order` tokens before it.
+    pub fn train<'a, I: IntoIterator<Item = &'a String>>(
+        lines: I,
+        unit: MarkovUnit,
+        order: usize,
+    ) -> Self {
+        let order = order.max(1);
+        let mut transitions: HashMap<Vec<String>, Vec<Option<String>>> = HashMap::new();
+        for line in lines {
+            let mut state = vec![String::new(); order];
+            for token in tokenize(line, unit) {
+                transitions
+                    .entry(state.clone())
+                    .or_default()
+                    .push(Some(token.clone()));
+                state.remove(0);
+                state.push(token);
+            }
+            transitions.entry(state).or_default().push(None);
+        }
+        MarkovModel {
+            unit,
+            order,
+            transitions,
+        }
+    }
+
+    /// Generates a line of at most `max_tokens` tokens, or `None` if the model
+    /// is empty.
+    pub fn generate<R: Rng>(&self, rng: &mut R, max_tokens: usize) -> Option<String> {
+        let mut state = vec![String::new(); self.order];
+        let mut tokens = Vec::new();
+        while tokens.len() < max_tokens {
+            match self.transitions.get(&state)?.choose(rng)? {
+                Some(token) => {
+                    tokens.push(token.clone());
+                    state.remove(0);
+                    state.push(token.clone());
+                }
+                None => break,
+            }
+        }
+        Some(match self.unit {
+            MarkovUnit::Words => tokens.join(" "),
+            MarkovUnit::Chars => tokens.concat(),
+        })
+    }
+}
+
+fn tokenize(line: &str, unit: MarkovUnit) -> Vec<String> {
+    match unit {
+        MarkovUnit::Words => line.split_whitespace().map(str::to_string).collect(),
+        MarkovUnit::Chars => line.chars().map(String::from).collect(),
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Trains a [`MarkovModel`] on every line of `key`.
+    pub fn markov_model(&self, key: &str, unit: MarkovUnit, order: usize) -> Option<MarkovModel> {
+        let lines = self.line_pairs.get(key)?;
+        Some(MarkovModel::train(lines, unit, order))
+    }
+
+    /// Generates a line in the style of the lines of `key`, preferring ones that
+    /// aren't copies of an existing line.
+    pub fn generate_markov_line(
+        &mut self,
+        key: &str,
+        unit: MarkovUnit,
+        order: usize,
+    ) -> Option<String> {
+        const ATTEMPTS: usize = 20;
+        const MAX_TOKENS: usize = 100;
+
+        let model = self.markov_model(key, unit, order)?;
+        let mut line = None;
+        for _ in 0..ATTEMPTS {
+            let generated = model.generate(&mut self.rng, MAX_TOKENS)?;
+            let novel = !self.line_pairs[key].contains(&generated);
+            line = Some(generated);
+            if novel {
+                break;
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_from_chain() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("books", "The Tale of the Dragon").unwrap();
+        ht.insert("books", "The Fall of the King").unwrap();
+
+        let model = ht.markov_model("books", MarkovUnit::Words, 1).unwrap();
+        for _ in 0..20 {
+            let title = model.generate(&mut rand::thread_rng(), 100).unwrap();
+            assert!(title.starts_with("The "));
+            assert!(title.ends_with("Dragon") || title.ends_with("King"));
+        }
+
+        let word = ht
+            .generate_markov_line("books", MarkovUnit::Chars, 2)
+            .unwrap();
+        assert!(!word.is_empty());
+        assert!(ht
+            .generate_markov_line("missing", MarkovUnit::Words, 1)
+            .is_none());
+    }
+}
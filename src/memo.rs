@@ -0,0 +1,168 @@
+//! Memoizing rendered lines, for strings re-rendered every frame with the
+//! same data.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::{hash, Escape, HotText};
+
+/// Rendered lines keyed by their template and a hash of their data.
+#[derive(Debug, Clone)]
+pub(crate) struct RenderMemo {
+    capacity: usize,
+    /// Nested so lookups can borrow the template rather than copy it.
+    renders: HashMap<String, HashMap<u64, String>>,
+    len: usize,
+}
+
+impl RenderMemo {
+    /// Hashes render data independently of its order, along with how its
+    /// values are escaped.
+    pub fn hash_data(data: &HashMap<&str, &str>, escape: Escape) -> u64 {
+        let mut pairs: Vec<(&&str, &&str)> = data.iter().collect();
+        pairs.sort_unstable();
+        let mut bytes = vec![escape as u8];
+        for (name, value) in pairs {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.push(0);
+        }
+        hash::fnv1a(&bytes)
+    }
+
+    /// Variant of [`RenderMemo::hash_data()`] for structured data, or `None`
+    /// if it holds lambdas, whose output can't be memoized.
+    pub fn hash_structured(data: &mustache::Data, escape: Escape) -> Option<u64> {
+        let mut bytes = vec![escape as u8];
+        encode_structured(data, &mut bytes)?;
+        Some(hash::fnv1a(&bytes))
+    }
+
+    pub fn get(&self, line: &str, data_hash: u64) -> Option<&String> {
+        self.renders.get(line)?.get(&data_hash)
+    }
+
+    pub fn clear(&mut self) {
+        self.renders.clear();
+        self.len = 0;
+    }
+
+    /// Stores a render, forgetting all others first if the memo is full.
+    pub fn insert(&mut self, line: &str, data_hash: u64, rendered: &str) {
+        if self.len >= self.capacity {
+            self.clear();
+        }
+        if self.capacity > 0 {
+            let renders = self.renders.entry(line.to_string()).or_default();
+            if renders.insert(data_hash, rendered.to_string()).is_none() {
+                self.len += 1;
+            }
+        }
+    }
+}
+
+/// Appends an unambiguous encoding of `data`, with map entries sorted by name.
+fn encode_structured(data: &mustache::Data, bytes: &mut Vec<u8>) -> Option<()> {
+    let encode_str = |s: &str, bytes: &mut Vec<u8>| {
+        bytes.extend_from_slice(&(s.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(s.as_bytes());
+    };
+    match data {
+        mustache::Data::Null => bytes.push(0),
+        mustache::Data::Bool(value) => bytes.extend_from_slice(&[1, *value as u8]),
+        mustache::Data::String(value) => {
+            bytes.push(2);
+            encode_str(value, bytes);
+        }
+        mustache::Data::Vec(items) => {
+            bytes.push(3);
+            bytes.extend_from_slice(&(items.len() as u64).to_le_bytes());
+            for item in items {
+                encode_structured(item, bytes)?;
+            }
+        }
+        mustache::Data::Map(map) => {
+            bytes.push(4);
+            bytes.extend_from_slice(&(map.len() as u64).to_le_bytes());
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(name, _)| *name);
+            for (name, value) in entries {
+                encode_str(name, bytes);
+                encode_structured(value, bytes)?;
+            }
+        }
+        mustache::Data::Fun(_) => return None,
+    }
+    Some(())
+}
+
+impl<R: Rng> HotText<R> {
+    /// Memoizes up to `capacity` results of [`HotText::render_line()`] by line
+    /// and data, so unchanged strings skip templating, or stops with `None`.
+    ///
+    /// Lines using helpers that depend on the current time, like
+    /// `{{ago timestamp}}`, keep their first result while memoized.
+    pub fn set_render_memo(&mut self, capacity: Option<usize>) {
+        self.render_memo = capacity.map(|capacity| RenderMemo {
+            capacity,
+            renders: HashMap::new(),
+            len: 0,
+        });
+    }
+
+    /// Chainable variant of [`HotText::set_render_memo()`]
+    pub fn with_render_memo(mut self, capacity: Option<usize>) -> Self {
+        self.set_render_memo(capacity);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memoize_renders() {
+        let mut ht = HotText::new(rand::thread_rng()).with_render_memo(Some(1));
+        ht.insert("hud.gold", "Gold: {{gold}}").unwrap();
+        for _ in 0..3 {
+            assert_eq!(
+                ht.render_line("hud.gold", vec![("gold", "12")]).unwrap(),
+                "Gold: 12"
+            );
+        }
+        assert_eq!(ht.render_memo.as_ref().unwrap().len, 1);
+        assert_eq!(
+            ht.render_line("hud.gold", vec![("gold", "13")]).unwrap(),
+            "Gold: 13"
+        );
+        let mut data = HashMap::new();
+        data.insert("gold", "13");
+        let memo = ht.render_memo.as_ref().unwrap();
+        assert_eq!(
+            memo.get("Gold: {{gold}}", RenderMemo::hash_data(&data, Escape::Html))
+                .unwrap(),
+            "Gold: 13"
+        );
+
+        ht.set_capitalization(crate::Capitalization::Sentence);
+        assert_eq!(ht.render_memo.as_ref().unwrap().len, 0);
+    }
+
+    #[test]
+    fn hash_structured_data() {
+        let hash = |value: serde_json::Value| {
+            RenderMemo::hash_structured(&mustache::to_data(value).unwrap(), Escape::Html)
+        };
+        let items = serde_json::json!({ "items": ["a", "b"], "open": true });
+
+        assert_eq!(hash(items.clone()), hash(items));
+        assert_ne!(
+            hash(serde_json::json!({ "items": ["ab"] })),
+            hash(serde_json::json!({ "items": ["a", "b"] }))
+        );
+        assert!(hash(serde_json::json!({ "open": true })).is_some());
+    }
+}
@@ -0,0 +1,110 @@
+//! Random name generation from syllable and part lists.
+//!
+//! Name parts are ordinary keys, e.g. `names.elf.prefix` and `names.elf.suffix`.
+//! A [`NameCulture`] combines them with patterns such as `{prefix}{suffix}`,
+//! where each `{part}` is replaced by a line drawn from `<key_prefix>.<part>`.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::HotText;
+
+/// How names are built for one culture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameCulture {
+    /// Prefix of the keys holding name parts, e.g. `names.elf`.
+    pub key_prefix: String,
+    /// Patterns of `{part}` placeholders and literal text, one chosen at random
+    /// for each name.
+    pub patterns: Vec<String>,
+    /// Uppercase the first letter of each word.
+    pub capitalize: bool,
+}
+
+impl NameCulture {
+    pub fn new(key_prefix: &str, patterns: &[&str]) -> Self {
+        NameCulture {
+            key_prefix: key_prefix.to_string(),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            capitalize: true,
+        }
+    }
+}
+
+/// Uppercases the first letter of every space-separated word.
+fn capitalize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut word_start = true;
+    for c in name.chars() {
+        if word_start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.push(c);
+        }
+        word_start = c == ' ' || c == '-';
+    }
+    out
+}
+
+impl<R: Rng> HotText<R> {
+    /// Registers a [`NameCulture`] under a name such as `elf`.
+    pub fn set_name_culture(&mut self, name: &str, culture: NameCulture) {
+        self.name_cultures.insert(name.to_string(), culture);
+    }
+
+    /// Chainable variant of [`HotText::set_name_culture()`]
+    pub fn with_name_culture(mut self, name: &str, culture: NameCulture) -> Self {
+        self.set_name_culture(name, culture);
+        self
+    }
+
+    /// Generates a name for a culture, or `None` if the culture or one of the
+    /// parts of the chosen pattern is missing.
+    pub fn generate_name(&mut self, culture: &str) -> Option<String> {
+        let culture = self.name_cultures.get(culture)?;
+        let pattern = culture.patterns.choose(&mut self.rng)?.clone();
+        let (key_prefix, capitalize_words) = (culture.key_prefix.clone(), culture.capitalize);
+
+        let mut name = String::new();
+        let mut rest = pattern.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}')?;
+            name.push_str(&rest[..start]);
+            let part = &rest[start + 1..end];
+            let key = if key_prefix.is_empty() {
+                part.to_string()
+            } else {
+                format!("{}.{}", key_prefix, part)
+            };
+            name.push_str(&self.get_line_raw(&key)?);
+            rest = &rest[end + 1..];
+        }
+        name.push_str(rest);
+
+        Some(if capitalize_words {
+            capitalize(&name)
+        } else {
+            name
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_name() {
+        let mut ht = HotText::new(rand::thread_rng()).with_name_culture(
+            "dwarf",
+            NameCulture::new("names.dwarf", &["{prefix}{suffix} {clan}"]),
+        );
+        ht.insert("names.dwarf.prefix", "thor").unwrap();
+        ht.insert("names.dwarf.suffix", "in").unwrap();
+        ht.insert("names.dwarf.clan", "oakenshield").unwrap();
+
+        assert_eq!(ht.generate_name("dwarf").unwrap(), "Thorin Oakenshield");
+        assert!(ht.generate_name("elf").is_none());
+        assert_eq!(capitalize("anne-marie de la cour"), "Anne-Marie De La Cour");
+    }
+}
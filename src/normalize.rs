@@ -0,0 +1,260 @@
+//! Text normalization applied to lines and keys as they are loaded.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use icu_normalizer::ComposingNormalizerBorrowed;
+use rand::Rng;
+
+use crate::HotText;
+
+/// Normalization steps applied to every line as it is inserted or loaded.
+///
+/// All steps are off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Trim leading and trailing whitespace.
+    pub trim: bool,
+    /// Collapse runs of spaces and tabs inside the line into a single space.
+    pub collapse_spaces: bool,
+    /// Apply Unicode Normalization Form C, composing base letters followed by
+    /// combining marks into their precomposed forms.
+    pub nfc: bool,
+    /// Replace curly quotes and apostrophes with their straight ASCII forms.
+    pub smart_quotes: bool,
+}
+
+impl NormalizeOptions {
+    /// Every normalization step enabled.
+    pub fn all() -> Self {
+        NormalizeOptions {
+            trim: true,
+            collapse_spaces: true,
+            nfc: true,
+            smart_quotes: true,
+        }
+    }
+
+    /// Applies the enabled steps to `line`.
+    pub fn apply<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        if self.smart_quotes && line.contains(is_smart_quote) {
+            line = Cow::Owned(line.chars().map(straighten_quote).collect());
+        }
+        if self.nfc {
+            if let Cow::Owned(composed) = ComposingNormalizerBorrowed::new_nfc().normalize(&line) {
+                line = Cow::Owned(composed);
+            }
+        }
+        if self.collapse_spaces && (line.contains("  ") || line.contains('\t')) {
+            let mut collapsed = String::with_capacity(line.len());
+            let mut previous_space = false;
+            for c in line.chars() {
+                let space = c == ' ' || c == '\t';
+                if !(space && previous_space) {
+                    collapsed.push(if space { ' ' } else { c });
+                }
+                previous_space = space;
+            }
+            line = Cow::Owned(collapsed);
+        }
+        if self.trim && line.trim().len() != line.len() {
+            line = Cow::Owned(line.trim().to_string());
+        }
+        line
+    }
+}
+
+/// Normalization applied to keys as lines are inserted and looked up, so
+/// keys from authors with different habits, like `Combat_Encounter ` and
+/// `combat.encounter`, name the same lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyNormalization {
+    /// Lowercase the key.
+    pub lowercase: bool,
+    /// Trim leading and trailing whitespace.
+    pub trim: bool,
+    /// Characters replaced with `separator`.
+    pub separators: String,
+    pub separator: char,
+}
+
+impl Default for KeyNormalization {
+    /// Lowercased and trimmed keys, with `_`, `/` and `:` replaced by `.`.
+    fn default() -> Self {
+        KeyNormalization {
+            lowercase: true,
+            trim: true,
+            separators: "_/:".to_string(),
+            separator: '.',
+        }
+    }
+}
+
+impl KeyNormalization {
+    /// Applies the normalization to `key`.
+    pub fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        let trimmed = if self.trim { key.trim() } else { key };
+        let mut normalized = String::with_capacity(trimmed.len());
+        for c in trimmed.chars() {
+            let c = if self.separators.contains(c) {
+                self.separator
+            } else {
+                c
+            };
+            if self.lowercase {
+                normalized.extend(c.to_lowercase());
+            } else {
+                normalized.push(c);
+            }
+        }
+        if normalized == key {
+            Cow::Borrowed(key)
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+}
+
+fn is_smart_quote(c: char) -> bool {
+    straighten_quote(c) != c
+}
+
+fn straighten_quote(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => '"',
+        c => c,
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the normalization applied to lines inserted or loaded from now on.
+    pub fn set_normalization(&mut self, options: NormalizeOptions) {
+        self.normalize = options;
+    }
+
+    /// Chainable variant of [`HotText::set_normalization()`]
+    pub fn with_normalization(mut self, options: NormalizeOptions) -> Self {
+        self.set_normalization(options);
+        self
+    }
+
+    /// Sets the normalization applied to keys inserted, loaded or looked up
+    /// from now on, or turns it off with `None`. Set it before loading
+    /// content, as keys already loaded aren't renamed.
+    pub fn set_key_normalization(&mut self, normalization: Option<KeyNormalization>) {
+        self.key_normalization = normalization;
+    }
+
+    /// Chainable variant of [`HotText::set_key_normalization()`]
+    pub fn with_key_normalization(mut self, normalization: KeyNormalization) -> Self {
+        self.set_key_normalization(Some(normalization));
+        self
+    }
+
+    pub(crate) fn normalize_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match &self.key_normalization {
+            Some(normalization) => normalization.apply(key),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    pub(crate) fn normalize_line(&self, line: &str) -> String {
+        self.normalize.apply(line).into_owned()
+    }
+
+    pub(crate) fn normalize_lines(&self, lines: HashSet<String>) -> HashSet<String> {
+        if self.normalize == NormalizeOptions::default() {
+            return lines;
+        }
+        lines.iter().map(|line| self.normalize_line(line)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let options = NormalizeOptions::all();
+
+        assert_eq!(options.apply("  Hello,\t  world!  "), "Hello, world!");
+        assert_eq!(options.apply("\u{201C}It\u{2019}s\u{201D}"), "\"It's\"");
+        assert_eq!(options.apply("Cafe\u{0301} cre\u{0300}me"), "Café crème");
+        assert_eq!(options.apply("x\u{0301}"), "x\u{0301}");
+        assert_eq!(options.apply("\u{1100}\u{1161}\u{11A8}"), "\u{AC01}");
+        assert_eq!(options.apply("a\u{0323}\u{0302}"), "\u{1EAD}");
+        assert_eq!(options.apply("\u{212B}"), "\u{00C5}");
+        assert!(matches!(options.apply("Untouched."), Cow::Borrowed(_)));
+        assert_eq!(NormalizeOptions::default().apply("  as is  "), "  as is  ");
+    }
+
+    #[test]
+    fn normalize_on_insert() {
+        let mut ht = HotText::new(rand::thread_rng()).with_normalization(NormalizeOptions::all());
+        ht.insert("greet", "Hello  there! ").unwrap();
+        ht.insert("greet", "Hello there!").unwrap();
+
+        assert_eq!(ht.get_lines_raw("greet").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn normalize_keys() {
+        let mut ht =
+            HotText::new(rand::thread_rng()).with_key_normalization(KeyNormalization::default());
+        ht.insert("Combat_Encounter ", "A lion!").unwrap();
+        ht.insert("combat.encounter", "A tiger!").unwrap();
+        ht.load_hashmap(
+            vec![(
+                "COMBAT/SLAY".to_string(),
+                vec!["Slain!".to_string()].into_iter().collect(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        assert_eq!(ht.get("combat.encounter").unwrap().len(), 2);
+        assert_eq!(ht.get_lines_raw("  COMBAT_ENCOUNTER").unwrap().len(), 2);
+        assert_eq!(ht.get_line_raw("Combat:Slay").unwrap(), "Slain!");
+        assert!(matches!(
+            KeyNormalization::default().apply("greet"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn normalize_per_key_settings() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_key_normalization(KeyNormalization::default())
+            .with_key_selection("NPC_Greet", crate::Selection::First)
+            .with_key_cooldown("npc/bark", crate::Cooldown::Draws(1))
+            .with_experiment(
+                "Shop:Greeting",
+                crate::Experiment::new().bucket("friendly", 100.0, "shop.greeting.friendly"),
+            )
+            .with_sequence("NPC_Intro", &["One.", "Two."])
+            .unwrap();
+        for line in &["Howdy!", "Hello!"] {
+            ht.insert("npc.greet", line).unwrap();
+        }
+        for line in &["Woof!", "Arf!"] {
+            ht.insert("npc.bark", line).unwrap();
+        }
+        ht.insert("shop.greeting.friendly", "Welcome, friend!")
+            .unwrap();
+
+        assert_eq!(ht.get_line_raw("npc.greet").unwrap(), "Hello!");
+        let bark = ht.get_line_raw("npc.bark").unwrap();
+        assert_ne!(ht.get_line_raw("npc.bark").unwrap(), bark);
+        assert_eq!(ht.bucket_for("shop.greeting", "user").unwrap(), "friendly");
+        assert_eq!(
+            ht.get_line_for_user("shop.greeting", "user").unwrap(),
+            "Welcome, friend!"
+        );
+        assert_eq!(ht.next_line("npc.intro").unwrap(), "One.");
+        assert_eq!(ht.next_line("NPC_Intro").unwrap(), "Two.");
+    }
+}
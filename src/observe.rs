@@ -0,0 +1,248 @@
+//! Notifying observers about which keys changed when content is reloaded.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::layer::Layer;
+use crate::shared::CowArc;
+use crate::{HotText, LineMetas, LinePairs};
+
+/// The keys whose lines changed, sent to observers after loads, patches,
+/// restores and layer changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Keys that had no lines before.
+    pub added: Vec<String>,
+    /// Keys that have no lines anymore.
+    pub removed: Vec<String>,
+    /// Keys whose lines differ.
+    pub modified: Vec<String>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Every changed key, sorted.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .added
+            .iter()
+            .chain(&self.removed)
+            .chain(&self.modified)
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn between(before: &LinePairs, after: &LinePairs) -> Self {
+        let mut changes = Changes::default();
+        for (key, lines) in after {
+            match before.get(key) {
+                None => changes.added.push(key.clone()),
+                Some(old) if old != lines => changes.modified.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.removed = before
+            .keys()
+            .filter(|key| !after.contains_key(*key))
+            .cloned()
+            .collect();
+        changes.added.sort_unstable();
+        changes.removed.sort_unstable();
+        changes.modified.sort_unstable();
+        changes
+    }
+}
+
+type ChangeCallback = Arc<dyn Fn(&Changes) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Observers {
+    callbacks: Vec<ChangeCallback>,
+    senders: Vec<Sender<Changes>>,
+    /// Whether a change is being observed, so nested changes aren't reported twice.
+    pub observing: bool,
+}
+
+impl Observers {
+    fn notify(&mut self, changes: &Changes) {
+        for callback in &self.callbacks {
+            callback(changes);
+        }
+        self.senders
+            .retain(|sender| sender.send(changes.clone()).is_ok());
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("callbacks", &self.callbacks.len())
+            .field("senders", &self.senders.len())
+            .finish()
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Adds a callback called with the [`Changes`] whenever content is loaded,
+    /// patched or restored, or a layer is loaded or switched, so dependent
+    /// systems can invalidate what they cached.
+    pub fn on_change<F: Fn(&Changes) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.observers.callbacks.push(Arc::new(callback));
+    }
+
+    /// Variant of [`HotText::on_change()`] that sends the [`Changes`] over a
+    /// channel, for observers on other threads. Dropping the receiver
+    /// unsubscribes it.
+    pub fn subscribe_changes(&mut self) -> Receiver<Changes> {
+        let (sender, receiver) = mpsc::channel();
+        self.observers.senders.push(sender);
+        receiver
+    }
+
+    /// The lines drawn for each key, across the base content and enabled layers.
+    fn effective_lines(&self) -> LinePairs {
+        let mut lines = LinePairs::clone(&self.line_pairs);
+        for layer in self.layers.iter().filter(|layer| layer.enabled) {
+            for (key, layer_lines) in &layer.line_pairs {
+                lines
+                    .entry(key.clone())
+                    .or_default()
+                    .extend(layer_lines.iter().cloned());
+            }
+        }
+        lines.retain(|_, lines| !lines.is_empty());
+        lines
+    }
+
+    /// Keys whose drawn line metadata differs from `line_meta` and `layers`.
+    fn meta_changes(
+        &self,
+        line_meta: &CowArc<LineMetas>,
+        layers: &CowArc<Vec<Layer>>,
+    ) -> Vec<String> {
+        if CowArc::ptr_eq(line_meta, &self.line_meta) && CowArc::ptr_eq(layers, &self.layers) {
+            return Vec::new();
+        }
+        let before = drawn_metas(line_meta, layers);
+        let after = drawn_metas(&self.line_meta, &self.layers);
+        let keys: HashSet<&String> = before
+            .iter()
+            .chain(&after)
+            .flat_map(|metas| metas.keys())
+            .collect();
+        keys.into_iter()
+            .filter(|key| {
+                !before
+                    .iter()
+                    .map(|metas| metas.get(*key))
+                    .eq(after.iter().map(|metas| metas.get(*key)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Runs `change`, then bumps the [generation](HotText::generation()) and
+    /// notifies observers if it changed any keys.
+    pub(crate) fn observe_changes<T>(&mut self, change: impl FnOnce(&mut Self) -> T) -> T {
+        if self.observers.observing {
+            return change(self);
+        }
+        self.observers.observing = true;
+        let before = self.effective_lines();
+        let (meta_before, layers_before) = (self.line_meta.clone(), self.layers.clone());
+        let result = change(self);
+        let changes = Changes::between(&before, &self.effective_lines());
+        for key in changes.keys() {
+            self.alias_tables.invalidate(key);
+        }
+        // Loads can change weights without changing lines.
+        for key in self.meta_changes(&meta_before, &layers_before) {
+            self.alias_tables.invalidate(&key);
+        }
+        self.observers.observing = false;
+        if !changes.is_empty() {
+            self.generations.bump(changes.keys());
+            self.observers.notify(&changes);
+        }
+        result
+    }
+}
+
+/// The metadata of the base content and enabled layers, in draw order.
+fn drawn_metas<'a>(line_meta: &'a LineMetas, layers: &'a [Layer]) -> Vec<&'a LineMetas> {
+    std::iter::once(line_meta)
+        .chain(
+            layers
+                .iter()
+                .filter(|layer| layer.enabled)
+                .map(|layer| &layer.line_meta),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Mutex;
+
+    #[test]
+    fn notify_observers() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("names", "Brom").unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let s = Arc::clone(&seen);
+        ht.on_change(move |changes| s.lock().unwrap().push(changes.clone()));
+        let receiver = ht.subscribe_changes();
+
+        ht.load("./test_lines.toml").unwrap();
+        let changes = receiver.try_recv().unwrap();
+        assert!(changes.added.contains(&"meta.welcome".to_string()));
+        assert!(changes.modified.is_empty());
+
+        ht.load_layer("holiday", "./test_includes/common.json")
+            .unwrap();
+        assert_eq!(receiver.try_recv().unwrap().modified, vec!["names"]);
+        assert!(ht.set_layer_enabled("holiday", false));
+        assert_eq!(receiver.try_recv().unwrap().keys(), vec!["names"]);
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(seen.lock().unwrap().len(), 3);
+
+        drop(receiver);
+        ht.set_layer_enabled("holiday", true);
+        assert_eq!(seen.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn redraw_changed_weights() {
+        let dir = std::env::temp_dir().join("hottext_observe_weights");
+        fs::create_dir_all(&dir).unwrap();
+        let (first, second) = (dir.join("first.json"), dir.join("second.json"));
+        fs::write(
+            &first,
+            r#"{ "schema_version": 2, "a": [{ "text": "x" }, { "text": "y", "weight": 0 }] }"#,
+        )
+        .unwrap();
+        fs::write(
+            &second,
+            r#"{ "schema_version": 2, "a": [{ "text": "x", "weight": 0 }, { "text": "y" }] }"#,
+        )
+        .unwrap();
+
+        let mut ht = HotText::new(rand::thread_rng()).with_load(&first).unwrap();
+        assert_eq!(ht.get_line_raw("a").unwrap(), "x");
+        let receiver = ht.subscribe_changes();
+        ht.load(&second).unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(ht.get_line_raw("a").unwrap(), "y");
+    }
+}
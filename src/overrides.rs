@@ -0,0 +1,134 @@
+//! Temporary boosts and suppressions of line weights, set at runtime.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::layer::{self, Layer};
+use crate::{HotText, LineMetas};
+
+/// Factors multiplied into the weights of specific lines and tagged lines.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overrides {
+    lines: HashMap<String, HashMap<String, f64>>,
+    tags: HashMap<String, f64>,
+}
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.tags.is_empty()
+    }
+
+    /// Multiplies the weights of a key's candidates by their overrides.
+    pub fn apply(
+        &self,
+        line_meta: &LineMetas,
+        layers: &[Layer],
+        key: &str,
+        candidates: &mut [(&String, f64)],
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        let lines = self.lines.get(key);
+        for (line, weight) in candidates.iter_mut() {
+            *weight *= lines.and_then(|lines| lines.get(*line)).unwrap_or(&1.0);
+            for (tag, factor) in &self.tags {
+                if layer::has_tag(line_meta, layers, key, line, tag) {
+                    *weight *= factor;
+                }
+            }
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Multiplies the weight of one line by `factor` until overrides are
+    /// cleared, e.g. `0.0` to suppress it. A factor of `1.0` removes the override.
+    pub fn set_line_override(&mut self, key: &str, line: &str, factor: f64) {
+        let key = self.normalize_key(key).into_owned();
+        let line = self.normalize_line(line);
+        let lines = self.overrides.lines.entry(key.clone()).or_default();
+        if factor == 1.0 {
+            lines.remove(&line);
+            if lines.is_empty() {
+                self.overrides.lines.remove(&key);
+            }
+        } else {
+            lines.insert(line, factor);
+        }
+        self.alias_tables.invalidate(&key);
+    }
+
+    /// Multiplies the weights of lines tagged `tag` by `factor` until
+    /// overrides are cleared, e.g. tripling `desert` lines while the player is
+    /// in the desert. A factor of `1.0` removes the override.
+    ///
+    /// Per-entity draws with [`HotText::get_line_for()`] ignore overrides, so
+    /// their assignments stay stable.
+    pub fn set_tag_override(&mut self, tag: &str, factor: f64) {
+        if factor == 1.0 {
+            self.overrides.tags.remove(tag);
+        } else {
+            self.overrides.tags.insert(tag.to_string(), factor);
+        }
+        self.alias_tables.clear();
+    }
+
+    /// Removes every line and tag override.
+    pub fn clear_overrides(&mut self) {
+        self.overrides = Overrides::default();
+        self.alias_tables.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyNormalization, NormalizeOptions};
+
+    #[test]
+    fn override_weights() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.entry("ambient")
+            .push_line("Sand stings your eyes.")
+            .unwrap()
+            .tag("desert")
+            .set_weight(0.0)
+            .push_line("Birds sing.")
+            .unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+        ht.insert("greet", "Hello!").unwrap();
+
+        assert_eq!(ht.get_line_raw("ambient").unwrap(), "Birds sing.");
+        ht.set_tag_override("desert", 3.0);
+        ht.set_line_override("ambient", "Birds sing.", 0.0);
+        ht.set_line_override("greet", "Hi!", 0.0);
+        for _ in 0..20 {
+            // A zero weight stays zero however much it is boosted.
+            assert!(ht.get_line_raw("ambient").is_none());
+            assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        }
+
+        ht.set_line_override("ambient", "Birds sing.", 1.0);
+        assert_eq!(ht.get_line_raw("ambient").unwrap(), "Birds sing.");
+        ht.clear_overrides();
+        assert!(ht.overrides.is_empty());
+    }
+
+    #[test]
+    fn normalize_overridden_lines() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_key_normalization(KeyNormalization::default())
+            .with_normalization(NormalizeOptions::all());
+        ht.insert("greet", "Hi!").unwrap();
+        ht.insert("greet", "Hello!").unwrap();
+
+        // Warm the alias table so the override has to invalidate it.
+        ht.get_line_raw("greet").unwrap();
+        ht.set_line_override("GREET", "  Hi! ", 0.0);
+        for _ in 0..20 {
+            assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Mustache partials resolved from other keys.
+
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// How deeply partials may include other partials, so cycles fail rather
+/// than recursing forever.
+const MAX_PARTIAL_DEPTH: usize = 8;
+
+/// A partial that couldn't be resolved.
+#[derive(Debug)]
+pub struct PartialError {
+    pub partial: String,
+    pub message: String,
+}
+
+impl fmt::Display for PartialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PartialError in `{}`: {}", self.partial, self.message)
+    }
+}
+
+impl std::error::Error for PartialError {}
+
+/// The keys named by the partials in `line`.
+pub(crate) fn partial_keys(line: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{>") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        keys.push(rest[start + 3..end].trim());
+        rest = &rest[end + 2..];
+    }
+    keys
+}
+
+impl<R: Rng> HotText<R> {
+    /// Replaces each partial like `{{> footer}}` with a line drawn from the key
+    /// it names, so shared fragments live in one place. Partials are rendered
+    /// with the same data as the line including them.
+    pub(crate) fn expand_partials(&mut self, line: String) -> Result<String, Box<dyn Error>> {
+        self.expand_partials_nested(line, 0)
+    }
+
+    fn expand_partials_nested(
+        &mut self,
+        line: String,
+        depth: usize,
+    ) -> Result<String, Box<dyn Error>> {
+        if !line.contains("{{>") {
+            return Ok(line);
+        }
+        let mut expanded = String::with_capacity(line.len());
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("{{>") {
+            let end = match rest[start..].find("}}") {
+                Some(end) => start + end,
+                None => break,
+            };
+            let partial = rest[start + 3..end].trim();
+            if depth >= MAX_PARTIAL_DEPTH {
+                return Err(PartialError {
+                    partial: partial.to_string(),
+                    message: "partials nest too deeply, there may be a cycle".to_string(),
+                }
+                .into());
+            }
+            let fragment = self.get_line_raw(partial).ok_or_else(|| PartialError {
+                partial: partial.to_string(),
+                message: "no lines for the key".to_string(),
+            })?;
+            expanded.push_str(&rest[..start]);
+            expanded.push_str(&self.expand_partials_nested(fragment, depth + 1)?);
+            rest = &rest[end + 2..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_partials() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("shop.buy", "That'll be {{gold}} gold.{{> shop.footer}}")
+            .unwrap();
+        ht.insert("shop.footer", " {{>shop.thanks}}").unwrap();
+        ht.insert("shop.thanks", "Thanks, {{name}}!").unwrap();
+        ht.insert("loop", "Again: {{> loop}}").unwrap();
+        ht.insert("broken", "{{> missing}}").unwrap();
+
+        assert_eq!(
+            ht.render_line("shop.buy", vec![("gold", "5"), ("name", "Brom")])
+                .unwrap(),
+            "That'll be 5 gold. Thanks, Brom!"
+        );
+        let error = ht.render_line("loop", vec![]).unwrap_err();
+        assert!(error.is::<PartialError>());
+        let error = ht.render_line("broken", vec![]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "PartialError in `missing`: no lines for the key"
+        );
+    }
+}
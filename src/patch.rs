@@ -0,0 +1,229 @@
+//! Patches that hotfix loaded content without redistributing it.
+//!
+//! A patch lists lines to add, lines to remove and keys to replace outright:
+//!
+//! ```toml
+//! [add]
+//! "combat.encounter" = ["You run into a wolf!"]
+//!
+//! [remove]
+//! "combat.encounter" = ["Oh no! It's a bear!"]
+//!
+//! [replace]
+//! "meta.welcome" = ["Welcome back, adventurer!"]
+//! ```
+//!
+//! Replacements are applied first, then removals, then additions.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{HotText, LinePairs};
+
+#[derive(Debug)]
+pub struct PatchError {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PatchError in `{}`: {}", self.key, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// A set of changes to apply on top of loaded content.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Patch {
+    /// Lines to add to their keys.
+    pub add: HashMap<String, HashSet<String>>,
+    /// Lines to remove from their keys. Every line must exist.
+    pub remove: HashMap<String, HashSet<String>>,
+    /// Keys whose lines are replaced entirely.
+    pub replace: HashMap<String, HashSet<String>>,
+}
+
+impl Patch {
+    /// Reads a patch from a JSON or TOML file, choosing the format by its extension.
+    pub fn load<P: AsRef<Path>>(file: P) -> Result<Self, Box<dyn Error>> {
+        let file = file.as_ref();
+        let content = fs::read_to_string(file)?;
+        match file.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(serde_json::from_str(&content)?),
+            Some("toml") => Ok(toml::from_str(&content)?),
+            _ => Err(PatchError {
+                key: String::new(),
+                message: format!("unsupported patch file `{}`", file.display()),
+            }
+            .into()),
+        }
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Applies a [`Patch`] to the collection.
+    ///
+    /// The patch is checked before anything is changed, so a patch removing a
+    /// line that doesn't exist fails with a [`PatchError`]. A patch that fails
+    /// part way, e.g. on an empty line under [`EmptyPolicy::Error`](crate::EmptyPolicy),
+    /// is rolled back, so either way the collection is left untouched.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), Box<dyn Error>> {
+        let replace: LinePairs = patch
+            .replace
+            .iter()
+            .map(|(key, lines)| {
+                let key = self.normalize_key(key).into_owned();
+                (key, self.normalize_lines(lines.clone()))
+            })
+            .collect();
+        for (key, lines) in &patch.remove {
+            let key = &self.normalize_key(key).into_owned();
+            for line in lines {
+                let line = self.normalize_line(line);
+                let exists = match replace.get(key) {
+                    Some(replaced) => replaced.contains(&line),
+                    None => self.line_pairs.get(key).is_some_and(|l| l.contains(&line)),
+                };
+                if !exists {
+                    return Err(PatchError {
+                        key: key.clone(),
+                        message: format!("no line {:?} to remove", line),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        self.transaction(|ht| {
+            for key in replace.keys() {
+                ht.line_pairs.remove(key);
+                ht.line_meta.remove(key);
+            }
+            ht.load_pairs(replace)?;
+
+            for (key, lines) in &patch.remove {
+                let key = &ht.normalize_key(key).into_owned();
+                for line in lines {
+                    let line = ht.normalize_line(line);
+                    if let Some(existing) = ht.line_pairs.get_mut(key) {
+                        existing.remove(&line);
+                    }
+                    if let Some(meta) = ht.line_meta.get_mut(key) {
+                        meta.remove(&line);
+                    }
+                }
+                // As with `HotText::remove()`, keys left without lines go.
+                if ht.line_pairs.get(key).is_some_and(HashSet::is_empty) {
+                    ht.line_pairs.remove(key);
+                }
+                if ht.line_meta.get(key).is_some_and(HashMap::is_empty) {
+                    ht.line_meta.remove(key);
+                }
+            }
+
+            ht.load_hashmap(patch.add.clone())
+        })
+    }
+
+    /// Reads a [`Patch`] from a file and applies it.
+    pub fn load_patch<P: AsRef<Path>>(&mut self, file: P) -> Result<(), Box<dyn Error>> {
+        let patch = Patch::load(file)?;
+        self.apply_patch(&patch)
+    }
+
+    /// Chainable variant of [`HotText::load_patch()`]
+    pub fn with_load_patch<P: AsRef<Path>>(mut self, file: P) -> Result<Self, Box<dyn Error>> {
+        self.load_patch(file)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_patch() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_load_toml("./test_lines.toml")
+            .unwrap();
+        let patch: Patch = toml::from_str(
+            "[add]\n\
+             \"combat.encounter\" = [\"You run into a wolf!\"]\n\
+             [remove]\n\
+             \"combat.encounter\" = [\"Oh no! It's a bear!\"]\n\
+             [replace]\n\
+             \"meta.welcome\" = [\"Welcome back!\"]\n",
+        )
+        .unwrap();
+        ht.apply_patch(&patch).unwrap();
+
+        let encounters = ht.get_lines_raw("combat.encounter").unwrap();
+        assert!(encounters.contains("You run into a wolf!"));
+        assert!(!encounters.contains("Oh no! It's a bear!"));
+        assert_eq!(ht.get_line_raw("meta.welcome").unwrap(), "Welcome back!");
+    }
+
+    #[test]
+    fn reject_missing_removal() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi!").unwrap();
+        let patch: Patch = serde_json::from_str(
+            r#"{ "replace": { "greet": ["Hello!"] }, "remove": { "greet": ["Hi!"] } }"#,
+        )
+        .unwrap();
+
+        assert!(ht.apply_patch(&patch).is_err());
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hi!");
+    }
+
+    #[test]
+    fn roll_back_failed_patch() {
+        let mut ht = HotText::new(rand::thread_rng()).with_empty_policy(crate::EmptyPolicy::Error);
+        ht.insert("greet", "Hi!").unwrap();
+        let patch: Patch = serde_json::from_str(r#"{ "replace": { "greet": [""] } }"#).unwrap();
+
+        assert!(ht.apply_patch(&patch).is_err());
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hi!");
+    }
+
+    #[test]
+    fn normalize_patched_keys() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_key_normalization(crate::KeyNormalization::default());
+        ht.insert("npc.greet", "Hi!").unwrap();
+        let patch: Patch = serde_json::from_str(
+            r#"{ "replace": { "NPC/Greet": ["Hello!", "Hey!"] }, "remove": { "npc.greet": ["Hey!"] } }"#,
+        )
+        .unwrap();
+
+        ht.apply_patch(&patch).unwrap();
+        assert_eq!(ht.get_line_raw("npc.greet").unwrap(), "Hello!");
+    }
+
+    #[test]
+    fn remove_emptied_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi!").unwrap();
+        let meta = crate::LineMeta {
+            weight: 2.0,
+            ..Default::default()
+        };
+        ht.insert_with_meta("bye", "Bye!", meta).unwrap();
+        let patch: Patch = serde_json::from_str(r#"{ "remove": { "bye": ["Bye!"] } }"#).unwrap();
+
+        ht.apply_patch(&patch).unwrap();
+        assert!(ht.get("bye").is_none());
+        assert!(!ht.line_meta.contains_key("bye"));
+        assert_eq!(ht.drawable_keys(), vec!["greet"]);
+    }
+}
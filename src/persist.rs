@@ -0,0 +1,293 @@
+//! Write-through persistence, so runtime edits survive restarts.
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use crate::format::{self, Format};
+use crate::{HotText, LineMetas, LinePairs, LoadWarning, LoadWarningKind};
+
+/// An error setting up the write-through file.
+#[derive(Debug)]
+pub struct WriteThroughError {
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for WriteThroughError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "WriteThroughError ({}): {}",
+            self.file.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for WriteThroughError {}
+
+/// The file that edits are written back to.
+#[derive(Debug, Clone)]
+pub(crate) struct WriteThrough {
+    file: PathBuf,
+    format: Format,
+}
+
+impl<R: Rng> HotText<R> {
+    /// Writes the base content back to `file` after every
+    /// [`HotText::insert()`], [`HotText::extend()`] and [`HotText::remove()`],
+    /// e.g. for in-game content editors and live-ops tweaks.
+    ///
+    /// The format is picked by file extension; JSON, TOML, YAML, plain text and
+    /// RON are supported. The file is rewritten in full, so comments and layers
+    /// are not kept. Line metadata is written as line objects in JSON and TOML;
+    /// the other formats can't hold it, so they refuse to write while any line
+    /// has metadata rather than drop it. Nothing is written until the next edit.
+    pub fn set_write_through<P: AsRef<Path>>(&mut self, file: P) -> Result<(), Box<dyn Error>> {
+        let file = file.as_ref();
+        let format = Format::from_path(file)
+            .filter(|format| {
+                format
+                    .serialize(&LinePairs::default(), &LineMetas::default())
+                    .is_some()
+            })
+            .ok_or_else(|| WriteThroughError {
+                file: file.to_path_buf(),
+                message: "unsupported file extension for writing".to_string(),
+            })?;
+        let write_through = WriteThrough {
+            file: file.to_path_buf(),
+            format,
+        };
+        self.check_meta(&write_through)?;
+        self.write_through = Some(write_through);
+        Ok(())
+    }
+
+    /// Chainable variant of [`HotText::set_write_through()`]
+    pub fn with_write_through<P: AsRef<Path>>(mut self, file: P) -> Result<Self, Box<dyn Error>> {
+        self.set_write_through(file)?;
+        Ok(self)
+    }
+
+    /// Stops writing edits back to a file.
+    pub fn clear_write_through(&mut self) {
+        self.write_through = None;
+    }
+
+    /// Writes the base content to the write-through file, if there is one.
+    ///
+    /// The content goes to a temporary file next to it first, which then
+    /// replaces it, so a crash or a full disk never leaves it half written.
+    pub(crate) fn persist(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(write_through) = &self.write_through {
+            self.check_meta(write_through)?;
+            let content = write_through
+                .format
+                .serialize(&self.line_pairs, &self.line_meta)
+                .expect("write-through formats serialize");
+            let temp = temp_path(&write_through.file);
+            let written = File::create(&temp).and_then(|mut file| {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            });
+            if let Err(error) = written.and_then(|_| fs::rename(&temp, &write_through.file)) {
+                let _ = fs::remove_file(&temp);
+                return Err(error.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `edits` with writing through deferred, then writes the file once
+    /// if they succeed. Observers are notified once for the whole batch.
+    pub(crate) fn batch<T>(
+        &mut self,
+        edits: impl FnOnce(&mut Self) -> Result<T, Box<dyn Error>>,
+    ) -> Result<T, Box<dyn Error>> {
+        let write_through = self.write_through.take();
+        let result = self.observe_changes(edits);
+        self.write_through = write_through;
+        let value = result?;
+        self.persist()?;
+        Ok(value)
+    }
+
+    /// Refuses to write line metadata to a format that would drop it.
+    fn check_meta(&self, write_through: &WriteThrough) -> Result<(), WriteThroughError> {
+        if write_through.format.writes_meta() || !self.line_meta.values().any(format::has_meta) {
+            return Ok(());
+        }
+        Err(WriteThroughError {
+            file: write_through.file.clone(),
+            message: "line metadata can only be written to JSON and TOML files".to_string(),
+        })
+    }
+
+    /// Variant of [`HotText::persist()`] for edits that can't fail, recording
+    /// a failure as a [`LoadWarningKind::WriteThroughFailed`] warning instead.
+    pub(crate) fn persist_or_warn(&mut self) {
+        if let Err(error) = self.persist() {
+            let file = self.write_through.as_ref().map(|w| w.file.clone());
+            self.warnings.push(LoadWarning {
+                file,
+                kind: LoadWarningKind::WriteThroughFailed {
+                    error: error.to_string(),
+                },
+            });
+        }
+    }
+}
+
+/// A hidden sibling of `file` to write its new content to, e.g.
+/// `.lines.json.tmp` for `lines.json`.
+fn temp_path(file: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(file.file_name().unwrap_or_default());
+    name.push(".tmp");
+    file.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineMeta;
+
+    #[test]
+    fn write_through() {
+        let dir = std::env::temp_dir().join("hottext_persist");
+        fs::create_dir_all(&dir).unwrap();
+        let mut names = vec!["lines.json", "lines.toml", "lines.yaml", "lines.txt"];
+        if cfg!(feature = "ron") {
+            names.push("lines.ron");
+        }
+        for name in names {
+            let file = dir.join(name);
+            let _ = fs::remove_file(&file);
+            let mut ht = HotText::new(rand::thread_rng())
+                .with_write_through(&file)
+                .unwrap();
+            ht.insert("combat.encounter", "A dragon!").unwrap();
+            ht.insert("greet", "Hi!").unwrap();
+            ht.insert("greet", "Hello!").unwrap();
+            assert!(ht.remove("greet", "Hi!").unwrap());
+            Extend::extend(&mut ht, vec![("bye", "Bye!")]);
+
+            let mut loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+            assert!(loaded.warnings().is_empty(), "{}", name);
+            assert_eq!(
+                loaded.get_line_raw("combat.encounter").unwrap(),
+                "A dragon!"
+            );
+            assert_eq!(loaded.get_lines_raw("greet").unwrap().len(), 1, "{}", name);
+            assert_eq!(loaded.get_line_raw("bye").unwrap(), "Bye!", "{}", name);
+        }
+
+        let mut ht = HotText::new(rand::thread_rng());
+        let error = ht.set_write_through(dir.join("lines.md")).unwrap_err();
+        assert!(error.is::<WriteThroughError>());
+    }
+
+    #[test]
+    fn keep_file_on_failed_write() {
+        let dir = std::env::temp_dir().join("hottext_persist_failed");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lines.json");
+        fs::write(&file, r#"{ "greet": ["Hi!"] }"#).unwrap();
+        // The temporary file can't be created where a directory is in the way.
+        fs::create_dir_all(temp_path(&file)).unwrap();
+
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_load(&file)
+            .unwrap()
+            .with_write_through(&file)
+            .unwrap();
+        assert!(ht.insert("greet", "Hello!").is_err());
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        assert_eq!(loaded.get("greet").unwrap().len(), 1);
+
+        Extend::extend(&mut ht, vec![("bye", "Bye!")]);
+        assert!(matches!(
+            ht.warnings().last().unwrap(),
+            LoadWarning { file: Some(warned), kind: LoadWarningKind::WriteThroughFailed { .. } }
+                if *warned == file
+        ));
+
+        fs::remove_dir(temp_path(&file)).unwrap();
+        ht.insert("greet", "Hey!").unwrap();
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        assert_eq!(loaded.get("greet").unwrap().len(), 3);
+        assert!(!temp_path(&file).exists());
+    }
+
+    #[test]
+    fn write_through_meta() {
+        let dir = std::env::temp_dir().join("hottext_persist_meta");
+        fs::create_dir_all(&dir).unwrap();
+        let meta = LineMeta {
+            weight: 2.5,
+            tags: vec!["formal".to_string()].into_iter().collect(),
+            id: Some("greet.hello".to_string()),
+        };
+        for name in &["lines.json", "lines.toml"] {
+            let file = dir.join(name);
+            let _ = fs::remove_file(&file);
+            let mut ht = HotText::new(rand::thread_rng())
+                .with_write_through(&file)
+                .unwrap();
+            ht.insert("greet", "Hi!").unwrap();
+            ht.insert_with_meta("greet", "Hello!", meta.clone())
+                .unwrap();
+            ht.insert("bye", "Bye!").unwrap();
+
+            let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+            assert!(loaded.warnings().is_empty(), "{}", name);
+            assert_eq!(loaded.line_meta("greet", "Hello!"), Some(&meta), "{}", name);
+            assert_eq!(loaded.get("greet").unwrap().len(), 2, "{}", name);
+            assert_eq!(loaded.get("bye").unwrap().len(), 1, "{}", name);
+        }
+
+        let file = dir.join("lines.yaml");
+        let _ = fs::remove_file(&file);
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_write_through(&file)
+            .unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+        let error = ht.insert_with_meta("greet", "Hello!", meta).unwrap_err();
+        assert!(error.is::<WriteThroughError>());
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        assert_eq!(loaded.get("greet").unwrap().len(), 1);
+        assert!(ht.set_write_through(&file).is_err());
+    }
+
+    #[test]
+    fn write_batch_once() {
+        let dir = std::env::temp_dir().join("hottext_persist_batch");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lines.json");
+        let _ = fs::remove_file(&file);
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_write_through(&file)
+            .unwrap();
+
+        ht.batch(|ht| {
+            ht.insert("greet", "Hi!")?;
+            assert!(!file.exists());
+            ht.insert("greet", "Hello!")
+        })
+        .unwrap();
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        assert_eq!(loaded.get("greet").unwrap().len(), 2);
+
+        ht.insert_sequence("intro", &["One.", "Two.", "Three."])
+            .unwrap();
+        let loaded = HotText::new(rand::thread_rng()).with_load(&file).unwrap();
+        assert_eq!(loaded.get("intro").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,228 @@
+//! Ordered post-processing of rendered lines.
+
+#[cfg(feature = "templates")]
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::emoji::{emoji, expand_shortcodes};
+use crate::filter::{ContentFilter, FilterError, Verdict};
+use crate::truncate::{grapheme_starts, truncate};
+use crate::HotText;
+#[cfg(feature = "templates")]
+use crate::RenderOptions;
+
+/// Expands a shortcode name, or returns `None` to keep it.
+type ShortcodeFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+#[derive(Clone)]
+enum Stage {
+    Map(Arc<dyn Fn(&str) -> String + Send + Sync>),
+    Filter(Arc<dyn ContentFilter + Send + Sync>),
+    Shortcodes(ShortcodeFn),
+    Wrap(usize),
+    Truncate(usize),
+}
+
+impl fmt::Debug for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Map(_) => write!(f, "Map"),
+            Stage::Filter(_) => write!(f, "Filter"),
+            Stage::Shortcodes(_) => write!(f, "Shortcodes"),
+            Stage::Wrap(width) => write!(f, "Wrap({})", width),
+            Stage::Truncate(width) => write!(f, "Truncate({})", width),
+        }
+    }
+}
+
+/// Text transforms applied in order to every rendered line, such as
+/// markup, then wrapping, then censoring, then truncation.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Adds a stage transforming the text with a closure, e.g. for markup.
+    pub fn map<F: Fn(&str) -> String + Send + Sync + 'static>(mut self, transform: F) -> Self {
+        self.stages.push(Stage::Map(Arc::new(transform)));
+        self
+    }
+
+    /// Adds a stage passing the text through a [`ContentFilter`].
+    pub fn filter<F: ContentFilter + Send + Sync + 'static>(mut self, filter: F) -> Self {
+        self.stages.push(Stage::Filter(Arc::new(filter)));
+        self
+    }
+
+    /// Adds a stage expanding `:sword:`-style shortcodes to Unicode emoji, so
+    /// content files can use readable names. Unknown shortcodes are kept.
+    pub fn emoji(self) -> Self {
+        self.shortcodes(|name| emoji(name).map(str::to_string))
+    }
+
+    /// Variant of [`Pipeline::emoji()`] expanding shortcodes with a closure,
+    /// e.g. to an engine's sprite tags. Shortcodes it returns `None` for are kept.
+    pub fn shortcodes<F: Fn(&str) -> Option<String> + Send + Sync + 'static>(
+        mut self,
+        expand: F,
+    ) -> Self {
+        self.stages.push(Stage::Shortcodes(Arc::new(expand)));
+        self
+    }
+
+    /// Adds a stage wrapping the text at spaces onto lines of at most `width`
+    /// visible characters. Longer words are left whole.
+    pub fn wrap(mut self, width: usize) -> Self {
+        self.stages.push(Stage::Wrap(width));
+        self
+    }
+
+    /// Adds a stage cutting the text to `width` visible characters with [`truncate()`].
+    pub fn truncate(mut self, width: usize) -> Self {
+        self.stages.push(Stage::Truncate(width));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Runs `text` through every stage in order.
+    pub fn apply(&self, text: String) -> Result<String, FilterError> {
+        self.stages
+            .iter()
+            .try_fold(text, |text, stage| match stage {
+                Stage::Map(transform) => Ok(transform(&text)),
+                Stage::Filter(filter) => match filter.filter(&text) {
+                    Verdict::Allow => Ok(text),
+                    Verdict::Replace(text) => Ok(text),
+                    Verdict::Reject(reason) => Err(FilterError { reason }),
+                },
+                Stage::Shortcodes(expand) => Ok(expand_shortcodes(&text, |name| expand(name))),
+                Stage::Wrap(width) => Ok(wrap(&text, *width)),
+                Stage::Truncate(width) => Ok(truncate(&text, *width)),
+            })
+    }
+}
+
+/// Greedily wraps each line of `text` at spaces.
+fn wrap(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut line_width = 0;
+        for word in line.split_whitespace() {
+            let word_width = grapheme_starts(word).len();
+            if line_width > 0 && line_width + 1 + word_width > width {
+                out.push('\n');
+                line_width = 0;
+            } else if line_width > 0 {
+                out.push(' ');
+                line_width += 1;
+            }
+            out.push_str(word);
+            line_width += word_width;
+        }
+    }
+    out
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the pipeline every rendered line passes through before it is
+    /// returned, replacing any previous one.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = pipeline;
+    }
+
+    /// Chainable variant of [`HotText::set_pipeline()`]
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.set_pipeline(pipeline);
+        self
+    }
+
+    pub fn pipeline(&self) -> &Pipeline {
+        &self.pipeline
+    }
+
+    /// Variant of [`HotText::render_line()`] that post-processes the line with
+    /// `pipeline` instead of the collection's own.
+    #[cfg(feature = "templates")]
+    pub fn render_line_with<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        data: D,
+        pipeline: &Pipeline,
+    ) -> Result<String, Box<dyn Error>> {
+        let data: Vec<(&str, &str)> = data.into_iter().collect();
+        let raw_line = self.draw_line(key);
+        let options = RenderOptions {
+            pipeline: Some(pipeline),
+            ..RenderOptions::default()
+        };
+        self.render_inner(key, raw_line, &data, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WordFilter;
+
+    #[test]
+    fn apply_stages() {
+        let pipeline = Pipeline::new()
+            .map(|text| text.replace("**", ""))
+            .wrap(12)
+            .filter(WordFilter::censor(vec!["darn"]))
+            .truncate(20);
+        assert_eq!(
+            pipeline
+                .apply("The **darn** goblin stole your lunch".to_string())
+                .unwrap(),
+            "The ****\ngoblin sto…"
+        );
+        assert_eq!(wrap("a b c\nd", 3), "a b\nc\nd");
+        assert_eq!(
+            Pipeline::new().apply("unchanged".to_string()).unwrap(),
+            "unchanged"
+        );
+        assert_eq!(
+            Pipeline::new()
+                .emoji()
+                .apply("Beware the :dragon: :unknown:".to_string())
+                .unwrap(),
+            "Beware the 🐉 :unknown:"
+        );
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn render_with_pipelines() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_pipeline(Pipeline::new().map(|text| text.to_uppercase()));
+        ht.insert("greeting", "Hello, {{name}}!").unwrap();
+        assert_eq!(
+            ht.render_line("greeting", vec![("name", "Jake")]).unwrap(),
+            "HELLO, JAKE!"
+        );
+        assert_eq!(
+            ht.render_line_with(
+                "greeting",
+                vec![("name", "Jake")],
+                &Pipeline::new().truncate(5)
+            )
+            .unwrap(),
+            "Hell…"
+        );
+    }
+}
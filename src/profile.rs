@@ -0,0 +1,115 @@
+//! Profile-specific content overrides.
+//!
+//! With a profile such as `dev` active, loading `lines.toml` also loads
+//! `lines.dev.toml` from the same directory when it exists, applying it on top
+//! of the base file.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use crate::{HotText, LinePairs};
+
+/// How a profile file is applied on top of its base file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileMerge {
+    /// Keys in the profile file replace the base file's lines for that key.
+    Replace,
+    /// Lines in the profile file are added to the base file's lines.
+    Extend,
+}
+
+/// The path of the profile variant of `file`, e.g. `lines.dev.toml` for `lines.toml`.
+pub(crate) fn profile_path(file: &Path, profile: &str) -> PathBuf {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match file.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, profile, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, profile),
+    };
+    file.with_file_name(name)
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets the active profile for subsequent loads, or clears it with `None`.
+    pub fn set_profile(&mut self, profile: Option<&str>, merge: ProfileMerge) {
+        self.profile = profile.map(|p| (p.to_string(), merge));
+    }
+
+    /// Chainable variant of [`HotText::set_profile()`]
+    pub fn with_profile(mut self, profile: &str, merge: ProfileMerge) -> Self {
+        self.set_profile(Some(profile), merge);
+        self
+    }
+
+    /// Gets the active profile, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_ref().map(|(profile, _)| profile.as_str())
+    }
+
+    /// Applies the lines of a profile file according to the active merge mode,
+    /// checking them like any other load.
+    pub(crate) fn load_profile_hashmap(
+        &mut self,
+        line_pairs: LinePairs,
+    ) -> Result<(), Box<dyn Error>> {
+        let replace = matches!(self.profile, Some((_, ProfileMerge::Replace)));
+        self.observe_changes(|ht| {
+            for (key, lines) in ht.prepare_pairs(line_pairs)? {
+                if replace {
+                    ht.line_pairs.remove(&key);
+                    ht.line_meta.remove(&key);
+                }
+                ht.check_lines(&key, &lines);
+                ht.line_pairs.entry(key).or_default().extend(lines);
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyContentError, EmptyPolicy, LoadWarning, LoadWarningKind};
+    use std::fs;
+
+    #[test]
+    fn profile_paths() {
+        assert_eq!(
+            profile_path(Path::new("content/lines.toml"), "dev"),
+            Path::new("content/lines.dev.toml")
+        );
+        assert_eq!(
+            profile_path(Path::new("lines"), "dev"),
+            Path::new("lines.dev")
+        );
+    }
+
+    #[test]
+    fn check_profile_lines() {
+        let dir = std::env::temp_dir().join("hottext_profile_checks");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lines.toml");
+        fs::write(&file, r#"greet = ["Hi!"]"#).unwrap();
+        fs::write(dir.join("lines.dev.toml"), r#"greet = ["", "Hey!"]"#).unwrap();
+
+        let ht = HotText::new(rand::thread_rng())
+            .with_profile("dev", ProfileMerge::Replace)
+            .with_load(&file)
+            .unwrap();
+        assert_eq!(ht.get("greet").unwrap().len(), 1);
+        assert!(ht.warnings().iter().any(|warning| matches!(
+            warning,
+            LoadWarning { file: Some(warned), kind: LoadWarningKind::EmptyLine { .. } }
+                if *warned == dir.join("lines.dev.toml")
+        )));
+
+        let error = HotText::new(rand::thread_rng())
+            .with_profile("dev", ProfileMerge::Extend)
+            .with_empty_policy(EmptyPolicy::Error)
+            .with_load(&file)
+            .unwrap_err();
+        assert!(error.is::<EmptyContentError>());
+    }
+}
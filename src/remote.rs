@@ -0,0 +1,126 @@
+//! Loading content from remote spreadsheets over HTTP and HTTPS.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+const MAX_REDIRECTS: u32 = 5;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct FetchError {
+    pub url: String,
+    pub message: String,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FetchError for {}: {}", self.url, self.message)
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Builds the CSV export URL of a Google Sheets document.
+///
+/// `gid` selects a worksheet; the first worksheet is used when it is `None`.
+pub fn google_sheets_csv_url(sheet_id: &str, gid: Option<&str>) -> String {
+    let mut url = format!(
+        "https://docs.google.com/spreadsheets/d/{}/export?format=csv",
+        sheet_id
+    );
+    if let Some(gid) = gid {
+        url.push_str("&gid=");
+        url.push_str(gid);
+    }
+    url
+}
+
+/// Fetches the body of `url` as a string, following redirects.
+pub(crate) fn fetch(url: &str) -> Result<String, Box<dyn Error>> {
+    fetch_with_timeout(url, TIMEOUT)
+}
+
+/// Variant of [`fetch()`] that gives up after `timeout` in total.
+fn fetch_with_timeout(url: &str, timeout: Duration) -> Result<String, Box<dyn Error>> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT.min(timeout))
+        .timeout(timeout)
+        .redirects(MAX_REDIRECTS)
+        .build();
+    let response = agent
+        .get(url)
+        .set("Accept", "text/csv, */*")
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => error(url, &format!("HTTP status {}", status)),
+            ureq::Error::Transport(transport) => match transport.message() {
+                Some(message) => error(url, &format!("{}: {}", transport.kind(), message)),
+                None => error(url, &transport.kind().to_string()),
+            },
+        })?;
+    Ok(response.into_string()?)
+}
+
+fn error(url: &str, message: &str) -> FetchError {
+    FetchError {
+        url: url.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn fetch_with_redirect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for (i, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let response = if i == 0 {
+                    "HTTP/1.0 302 Found\r\nLocation: /sheet.csv\r\n\r\n"
+                } else {
+                    "HTTP/1.0 200 OK\r\nContent-Type: text/csv\r\n\r\nkey,text\ngreet,Hello!\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let body = fetch(&format!("http://{}/", address)).unwrap();
+        assert_eq!(body, "key,text\ngreet,Hello!\n");
+    }
+
+    #[test]
+    fn time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_secs(5));
+        });
+
+        let url = format!("http://{}/", address);
+        assert!(fetch_with_timeout(&url, Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn sheets_url() {
+        assert_eq!(
+            google_sheets_csv_url("abc", Some("0")),
+            "https://docs.google.com/spreadsheets/d/abc/export?format=csv&gid=0"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Stable text reports of all content, for snapshot tests and content review.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use rand::Rng;
+
+use crate::{is_plain, Compiled, HotText};
+
+impl<R: Rng> HotText<R> {
+    /// Renders every line of every key with the sample `data` into a report
+    /// sorted by key and line, so it can be checked in as a snapshot and
+    /// content changes show up as reviewable diffs.
+    ///
+    /// Each line is listed as written, followed by its rendered form if that
+    /// differs, or the error if it fails to render. Nothing is drawn, so
+    /// history and cooldowns are unaffected.
+    pub fn render_report(&self, data: &[(&str, &str)]) -> String {
+        let data: HashMap<&str, &str> = data.iter().copied().collect();
+        let mut report = String::new();
+        for key in self.drawable_keys() {
+            let mut lines: Vec<&String> = std::iter::once(&*self.line_pairs)
+                .chain(
+                    self.layers
+                        .iter()
+                        .filter(|layer| layer.enabled)
+                        .map(|layer| &layer.line_pairs),
+                )
+                .filter_map(|line_pairs| line_pairs.get(key))
+                .flatten()
+                .collect();
+            lines.sort();
+            lines.dedup();
+
+            writeln!(report, "{}", key).expect("writing to a String");
+            for line in lines {
+                writeln!(report, "  - {}", line).expect("writing to a String");
+                let rendered = if is_plain(line) {
+                    Ok(line.clone())
+                } else {
+                    Compiled::new(line, self.capitalization, &self.locale)
+                        .and_then(|template| template.render_to_string(&data))
+                };
+                match rendered.and_then(|rendered| Ok(self.pipeline.apply(rendered)?)) {
+                    Ok(rendered) if rendered == *line => {}
+                    Ok(rendered) => {
+                        writeln!(report, "    = {}", rendered).expect("writing to a String")
+                    }
+                    Err(error) => writeln!(report, "    ! {}", error).expect("writing to a String"),
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_all_lines() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi, {{name}}!").unwrap();
+        ht.insert("greet", "Hello.").unwrap();
+        ht.insert("loot", "{{number gold}} gold").unwrap();
+        ht.insert("combat", "Fight!").unwrap();
+
+        assert_eq!(
+            ht.render_report(&[("name", "Jake")]),
+            "combat\n  - Fight!\n\
+             greet\n  - Hello.\n  - Hi, {{name}}!\n    = Hi, Jake!\n\
+             loot\n  - {{number gold}} gold\n    ! HelperError in `number`: no value for `gold`\n"
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! RON content files.
+//!
+//! ```ron
+//! {
+//!     // Comments and trailing commas are fine.
+//!     "combat.encounter": [
+//!         "You encounter a lion!",
+//!         r#"Oh no! It's a "bear"!"#,
+//!     ],
+//! }
+//! ```
+//!
+//! RON is translated into JSON before being deserialized, preserving line
+//! breaks so that error positions still point into the original file.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use crate::LinePairs;
+
+#[derive(Debug)]
+pub struct RonParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RonParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RonParseError on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RonParseError {}
+
+/// Deserializes RON content.
+pub(crate) fn parse<T: DeserializeOwned>(content: &str) -> Result<T, Box<dyn Error>> {
+    Ok(serde_json::from_str(&to_json(content)?)?)
+}
+
+/// Serializes key/line pairs into RON, sorted for stable diffs.
+pub(crate) fn to_string(line_pairs: &LinePairs) -> String {
+    let mut keys: Vec<&String> = line_pairs.keys().collect();
+    keys.sort();
+
+    let mut out = String::from("{\n");
+    for key in keys {
+        out.push_str(&format!("    {}: [\n", quote(key)));
+        let mut lines: Vec<&String> = line_pairs[key].iter().collect();
+        lines.sort();
+        for line in lines {
+            out.push_str(&format!("        {},\n", quote(line)));
+        }
+        out.push_str("    ],\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Translates RON into equivalent JSON.
+fn to_json(content: &str) -> Result<String, RonParseError> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut line = 1;
+    let mut i = 0;
+
+    let error = |line: usize, message: &str| RonParseError {
+        line,
+        message: message.to_string(),
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let start = line;
+                let mut depth = 0;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (Some('/'), Some('*')) => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        (Some('*'), Some('/')) => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        (Some('\n'), _) => {
+                            out.push('\n');
+                            line += 1;
+                            i += 1;
+                        }
+                        (Some(_), _) => i += 1,
+                        (None, _) => return Err(error(start, "unterminated block comment")),
+                    }
+                }
+            }
+            'r' if matches!(chars.get(i + 1), Some('"') | Some('#')) => {
+                let start = line;
+                let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+                i += 1 + hashes;
+                if chars.get(i) != Some(&'"') {
+                    return Err(error(line, "malformed raw string"));
+                }
+                i += 1;
+                let mut text = String::new();
+                loop {
+                    match chars.get(i) {
+                        Some('"')
+                            if chars[i + 1..]
+                                .iter()
+                                .take(hashes)
+                                .filter(|&&c| c == '#')
+                                .count()
+                                == hashes =>
+                        {
+                            i += 1 + hashes;
+                            break;
+                        }
+                        Some(&c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                            i += 1;
+                        }
+                        None => return Err(error(start, "unterminated raw string")),
+                    }
+                }
+                out.push_str(&serde_json::to_string(&text).unwrap_or_default());
+                out.push_str(&"\n".repeat(line - start));
+                continue;
+            }
+            '"' => {
+                let start = line;
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some('"') => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some('n') => text.push('\n'),
+                                Some('t') => text.push('\t'),
+                                Some('r') => text.push('\r'),
+                                Some('0') => text.push('\0'),
+                                Some('u') if chars.get(i + 1) == Some(&'{') => {
+                                    let end = chars[i..]
+                                        .iter()
+                                        .position(|&c| c == '}')
+                                        .ok_or_else(|| error(line, "malformed unicode escape"))?;
+                                    let hex: String = chars[i + 2..i + end].iter().collect();
+                                    let c = u32::from_str_radix(&hex, 16)
+                                        .ok()
+                                        .and_then(char::from_u32)
+                                        .ok_or_else(|| error(line, "invalid unicode escape"))?;
+                                    text.push(c);
+                                    i += end;
+                                }
+                                Some('\n') => {
+                                    // A backslash before a newline continues the string.
+                                    line += 1;
+                                    while chars.get(i + 1).is_some_and(|c| c.is_whitespace()) {
+                                        i += 1;
+                                        if chars[i] == '\n' {
+                                            line += 1;
+                                        }
+                                    }
+                                }
+                                Some(&c) => text.push(c),
+                                None => return Err(error(start, "unterminated string")),
+                            }
+                        }
+                        Some(&c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                        None => return Err(error(start, "unterminated string")),
+                    }
+                    i += 1;
+                }
+                i += 1;
+                out.push_str(&serde_json::to_string(&text).unwrap_or_default());
+                // Keep the line count of the original so error positions hold.
+                out.push_str(&"\n".repeat(line - start));
+                continue;
+            }
+            '(' => out.push('['),
+            ')' => out.push(']'),
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                out.push(c);
+            }
+        }
+        i += 1;
+    }
+
+    Ok(strip_trailing_commas(&out))
+}
+
+/// Removes commas directly preceding a closing bracket in JSON text.
+fn strip_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some(']') | Some('}')) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ron() {
+        let line_pairs: LinePairs = parse(
+            "/* Dungeon /* nested */ lines */\n\
+             {\n\
+             \x20   // Greetings\n\
+             \x20   \"meta.welcome\": [\"Welcome!\"],\n\
+             \x20   \"combat.encounter\": [\n\
+             \x20       \"You encounter a lion!\\u{1F981}\", // Roar.\n\
+             \x20       r#\"Oh no! It's a \"bear\"!\"#,\n\
+             \x20   ],\n\
+             }\n",
+        )
+        .unwrap();
+
+        assert!(line_pairs["meta.welcome"].contains("Welcome!"));
+        assert!(line_pairs["combat.encounter"].contains("You encounter a lion!\u{1F981}"));
+        assert!(line_pairs["combat.encounter"].contains("Oh no! It's a \"bear\"!"));
+    }
+
+    #[test]
+    fn parse_ron_errors() {
+        assert_eq!(to_json("{\n\"a\": [\"open\n]}").unwrap_err().line, 2);
+        assert!(parse::<LinePairs>("{ \"a\": [1] }").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let line_pairs: LinePairs =
+            parse("{\"a\": [\"one\", \"tab\\there\\u{7}\"], \"b\": []}").unwrap();
+        assert_eq!(
+            parse::<LinePairs>(&to_string(&line_pairs)).unwrap(),
+            line_pairs
+        );
+    }
+}
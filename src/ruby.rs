@@ -0,0 +1,123 @@
+//! Ruby annotations like furigana, written as `{漢字|かんじ}`.
+
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// A piece of a rendered line: plain text, or base text annotated with its
+/// reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Span {
+    Text(String),
+    Ruby { base: String, reading: String },
+}
+
+/// Splits `text` into plain text and ruby annotations written as
+/// `{base|reading}`. Braces that don't form an annotation are kept as text.
+pub fn ruby_spans(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let annotation = rest[start + 1..]
+            .find('}')
+            .map(|end| &rest[start + 1..start + 1 + end])
+            .filter(|inner| !inner.contains(['{', '\n']))
+            .and_then(|inner| inner.split_once('|'))
+            .filter(|(base, reading)| !base.is_empty() && !reading.is_empty());
+        match annotation {
+            Some((base, reading)) => {
+                plain.push_str(&rest[..start]);
+                if !plain.is_empty() {
+                    spans.push(Span::Text(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::Ruby {
+                    base: base.to_string(),
+                    reading: reading.to_string(),
+                });
+                rest = &rest[start + base.len() + reading.len() + 3..];
+            }
+            None => {
+                plain.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    plain.push_str(rest);
+    if !plain.is_empty() {
+        spans.push(Span::Text(plain));
+    }
+    spans
+}
+
+/// Drops the readings of ruby annotations, for renderers without ruby support.
+pub fn strip_ruby(text: &str) -> String {
+    ruby_spans(text)
+        .into_iter()
+        .map(|span| match span {
+            Span::Text(text) => text,
+            Span::Ruby { base, .. } => base,
+        })
+        .collect()
+}
+
+impl<R: Rng> HotText<R> {
+    /// Variant of [`HotText::render_line()`] that splits the rendered line into
+    /// [`Span`]s of plain text and ruby annotations, e.g. for furigana above
+    /// kanji written as `{漢字|かんじ}`.
+    #[cfg(feature = "templates")]
+    pub fn render_line_spans<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        data: D,
+    ) -> Result<Vec<Span>, Box<dyn Error>> {
+        Ok(ruby_spans(&self.render_line(key, data)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ruby() {
+        assert_eq!(
+            ruby_spans("{漢字|かんじ}を{読|よ}む {not ruby} {|x}"),
+            vec![
+                Span::Ruby {
+                    base: "漢字".to_string(),
+                    reading: "かんじ".to_string()
+                },
+                Span::Text("を".to_string()),
+                Span::Ruby {
+                    base: "読".to_string(),
+                    reading: "よ".to_string()
+                },
+                Span::Text("む {not ruby} {|x}".to_string()),
+            ]
+        );
+        assert_eq!(strip_ruby("{東京|とうきょう}へ行く"), "東京へ行く");
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn render_spans() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "{{name}}さん、{今日|きょう}は").unwrap();
+        assert_eq!(
+            ht.render_line_spans("greet", vec![("name", "ジェイク")])
+                .unwrap(),
+            vec![
+                Span::Text("ジェイクさん、".to_string()),
+                Span::Ruby {
+                    base: "今日".to_string(),
+                    reading: "きょう".to_string()
+                },
+                Span::Text("は".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Sampling keys at random and lines pooled from several keys.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::{layer, telemetry, HotText, Selection};
+
+impl<R: Rng> HotText<R> {
+    /// Gets one line pooled from several keys, e.g. `combat.taunt` and
+    /// `combat.generic`, without duplicating lines into a combined key.
+    ///
+    /// Each line's [`LineMeta::weight`](crate::LineMeta::weight) is multiplied by the weight of its key,
+    /// if `weights` are given in the same order as `keys`. Keys without lines
+    /// follow their fallbacks.
+    pub fn get_line_from(&mut self, keys: &[&str], weights: Option<&[f64]>) -> Option<String> {
+        let keys: Vec<(String, f64)> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let key = self.resolve_key(key).unwrap_or_else(|| key.to_string());
+                let weight = weights.and_then(|w| w.get(i)).copied().unwrap_or(1.0);
+                (key, weight)
+            })
+            .collect();
+        self.draw_pooled(&keys, None)
+    }
+
+    /// Gets one line tagged `tag` from any key, e.g. `get_line_by_tag("sarcastic")`
+    /// for personality-driven selection across topics.
+    pub fn get_line_by_tag(&mut self, tag: &str) -> Option<String> {
+        let keys: Vec<(String, f64)> = self
+            .drawable_keys()
+            .into_iter()
+            .map(|key| (key.clone(), 1.0))
+            .collect();
+        self.draw_pooled(&keys, Some(tag))
+    }
+
+    /// Draws one line pooled from several weighted keys, only considering
+    /// lines tagged `tag` if given.
+    pub(crate) fn draw_pooled(
+        &mut self,
+        keys: &[(String, f64)],
+        tag: Option<&str>,
+    ) -> Option<String> {
+        let (line_pairs, line_meta, layers) = (&self.line_pairs, &self.line_meta, &self.layers);
+        let overrides = &self.overrides;
+        let mut candidates: Vec<(&String, &String, f64)> = keys
+            .iter()
+            .flat_map(|(key, key_weight)| {
+                let mut candidates = layer::candidates(line_pairs, line_meta, layers, key);
+                overrides.apply(line_meta, layers, key, &mut candidates);
+                candidates
+                    .into_iter()
+                    .map(move |(line, weight)| (key, line, weight * key_weight))
+            })
+            .filter(|(key, line, _)| {
+                tag.is_none_or(|tag| layer::has_tag(line_meta, layers, key, line, tag))
+            })
+            .collect();
+        candidates.sort_unstable_by_key(|(key, line, _)| (*line, *key));
+        self.cooldowns.retain(&mut candidates, |(key, line, _)| {
+            (key.as_str(), line.as_str())
+        });
+        for (key, line, weight) in candidates.iter_mut() {
+            *weight *= self.cooldowns.recency_factor(key, line);
+        }
+        let chosen = match self.selection {
+            Selection::Random => candidates
+                .choose_weighted(&mut self.rng, |(_, _, weight)| *weight)
+                .ok(),
+            selection => selection.pick(
+                &candidates,
+                |(key, line, _)| (*line, *key),
+                |(_, _, weight)| *weight,
+            ),
+        };
+        let (key, line) = chosen.map(|(key, line, _)| ((*key).clone(), (*line).clone()))?;
+        self.record_draw(&key, &line);
+        self.notify_draw(&key, &line);
+        self.cooldowns.record(&key, &line);
+        Some(line)
+    }
+
+    /// Gets up to `count` different lines of a key, e.g. three rumors for a shop
+    /// to mention. Lines are drawn without replacement by their
+    /// [`LineMeta::weight`](crate::LineMeta::weight), so rarer lines stay rare.
+    pub fn get_distinct_lines(&mut self, key: &str, count: usize) -> Vec<String> {
+        let resolved = self.resolve_key(key);
+        let key = resolved.as_deref().unwrap_or(key);
+        let mut candidates =
+            layer::candidates(&self.line_pairs, &self.line_meta, &self.layers, key);
+        self.overrides
+            .apply(&self.line_meta, &self.layers, key, &mut candidates);
+        if candidates.is_empty() {
+            self.record_miss(key, telemetry::MissKind::MissingKey, &[]);
+            return Vec::new();
+        }
+        for (line, weight) in candidates.iter_mut() {
+            *weight *= self.cooldowns.recency_factor(key, line);
+        }
+        // Efraimidis-Spirakis: the lines with the largest `u^(1 / weight)` win.
+        let rng = &mut self.rng;
+        let mut scored: Vec<(f64, &String)> = candidates
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(line, weight)| (rng.gen::<f64>().powf(1.0 / weight), line))
+            .collect();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        let lines: Vec<String> = scored
+            .into_iter()
+            .take(count)
+            .map(|(_, line)| line.clone())
+            .collect();
+        for line in &lines {
+            self.record_draw(key, line);
+            self.notify_draw(key, line);
+            self.cooldowns.record(key, line);
+        }
+        lines
+    }
+
+    /// Picks a key at random from the base content and enabled layers.
+    pub fn random_key(&mut self) -> Option<String> {
+        self.random_key_with_prefix("")
+    }
+
+    /// Picks a key starting with `prefix` at random, e.g. any key under
+    /// `rumors.` for "say something from any rumor category".
+    ///
+    /// Follows the collection's [`Selection`], so deterministic modes always
+    /// pick the same key.
+    pub fn random_key_with_prefix(&mut self, prefix: &str) -> Option<String> {
+        let mut keys: Vec<&String> = self
+            .line_pairs
+            .keys()
+            .chain(
+                self.layers
+                    .iter()
+                    .filter(|layer| layer.enabled)
+                    .flat_map(|layer| layer.line_pairs.keys()),
+            )
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        // Sorted so a seeded RNG picks the same key regardless of hash order.
+        keys.sort();
+        keys.dedup();
+        let key = match self.selection {
+            Selection::Random => keys.choose(&mut self.rng),
+            selection => selection.pick(&keys, |key| *key, |_| 1.0),
+        };
+        key.map(|key| key.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("rumors.king", "The king is ill.").unwrap();
+        ht.insert("rumors.dragon", "A dragon was seen.").unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+
+        for _ in 0..20 {
+            assert!(ht
+                .random_key_with_prefix("rumors.")
+                .unwrap()
+                .starts_with("rumors."));
+        }
+        assert!(ht.random_key().is_some());
+        assert!(ht.random_key_with_prefix("quests.").is_none());
+
+        ht.set_selection(Selection::First);
+        assert_eq!(ht.random_key().unwrap(), "greet");
+    }
+
+    #[test]
+    fn pool_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("combat.taunt", "Come at me!").unwrap();
+        ht.insert("combat.generic", "Hyah!").unwrap();
+        ht.insert("greet", "Hi!").unwrap();
+        ht.set_fallback("combat.orc", "combat.generic");
+
+        for _ in 0..20 {
+            let line = ht
+                .get_line_from(&["combat.taunt", "combat.orc"], None)
+                .unwrap();
+            assert!(line == "Come at me!" || line == "Hyah!");
+            assert_eq!(
+                ht.get_line_from(&["combat.taunt", "greet"], Some(&[0.0, 1.0]))
+                    .unwrap(),
+                "Hi!"
+            );
+        }
+        assert!(ht.get_line_from(&["missing"], None).is_none());
+    }
+
+    #[test]
+    fn distinct_lines() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.entry("rumors")
+            .push_line("The king is ill.")
+            .unwrap()
+            .push_line("A dragon was seen.")
+            .unwrap()
+            .push_line("The mine is cursed.")
+            .unwrap()
+            .set_weight(0.0);
+
+        for _ in 0..20 {
+            let mut lines = ht.get_distinct_lines("rumors", 3);
+            lines.sort();
+            assert_eq!(lines, vec!["A dragon was seen.", "The king is ill."]);
+        }
+        assert_eq!(ht.get_distinct_lines("rumors", 1).len(), 1);
+        assert!(ht.get_distinct_lines("missing", 3).is_empty());
+    }
+
+    #[test]
+    fn sample_by_tag() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.entry("greet")
+            .push_line("Oh, it's you.")
+            .unwrap()
+            .tag("sarcastic")
+            .push_line("Welcome!")
+            .unwrap();
+        ht.entry("combat")
+            .push_line("Wow, so scary.")
+            .unwrap()
+            .tag("sarcastic")
+            .push_line("Have at you!")
+            .unwrap();
+
+        for _ in 0..20 {
+            let line = ht.get_line_by_tag("sarcastic").unwrap();
+            assert!(line == "Oh, it's you." || line == "Wow, so scary.");
+        }
+        assert!(ht.get_line_by_tag("polite").is_none());
+    }
+}
@@ -0,0 +1,517 @@
+//! Versioned content schema.
+//!
+//! Content files declare their schema with a top-level `schema_version` field.
+//! Files without one are treated as version 1 and still load, with a
+//! [`LoadWarningKind::OutdatedSchema`](crate::LoadWarningKind::OutdatedSchema)
+//! warning.
+//!
+//! # Versions
+//!
+//! - **1**: every key maps to a list of lines (or a single line).
+//! - **2**: lines may also be objects carrying per-line metadata, e.g.
+//!   `{ text = "Oh my, it's a dragon!", weight = 0.1, tags = ["rare"], id = "dragon" }`.
+//!
+//! # Upgrading from version 1
+//!
+//! Add `schema_version = 2` (or `"schema_version": 2`, `schema_version: 2`) at
+//! the top of the file. Version 1 content is valid version 2 content, so
+//! nothing else needs to change.
+//!
+//! # Editor support
+//!
+//! [`json_schema()`] describes the current version as a JSON Schema, which
+//! editors can validate and complete JSON, YAML and TOML content files with.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+
+use rand::Rng;
+use serde_json::{json, Value};
+
+use crate::{HotText, LineMeta, LineMetas, LinePairs, INCLUDE_KEY, MAX_LENGTH_KEY};
+
+/// The schema version written by this version of the crate.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The reserved key declaring a file's schema version.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+#[derive(Debug)]
+pub struct SchemaError {
+    pub key: Option<String>,
+    /// Where in the key's value the error is, e.g. `[1].weight` for the
+    /// weight of its second line.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(
+                f,
+                "SchemaError in `{}{}`: {}",
+                key,
+                self.path.as_deref().unwrap_or_default(),
+                self.message
+            ),
+            None => write!(f, "SchemaError: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Lines repeated within a key of one file, as `(key, line)` pairs.
+pub(crate) type Duplicates = Vec<(String, String)>;
+
+/// The parsed content of one file, migrated to the current schema.
+#[derive(Debug, Default)]
+pub(crate) struct Content {
+    /// The version the file declared, if any.
+    pub schema_version: Option<u32>,
+    pub line_pairs: LinePairs,
+    pub line_meta: LineMetas,
+    /// Maximum lengths by key pattern, see [`MAX_LENGTH_KEY`].
+    pub max_lengths: Vec<(String, usize)>,
+    pub duplicates: Duplicates,
+}
+
+fn error(key: Option<&str>, message: impl Into<String>) -> SchemaError {
+    SchemaError {
+        key: key.map(str::to_string),
+        path: None,
+        message: message.into(),
+    }
+}
+
+fn error_at(key: &str, path: String, message: impl Into<String>) -> SchemaError {
+    SchemaError {
+        path: Some(path),
+        ..error(Some(key), message)
+    }
+}
+
+fn check_version(version: Option<u32>) -> Result<(), SchemaError> {
+    match version {
+        Some(version) if version > SCHEMA_VERSION => Err(error(
+            None,
+            format!(
+                "schema version {} is newer than the supported version {}",
+                version, SCHEMA_VERSION
+            ),
+        )),
+        Some(0) => Err(error(None, "schema version 0 does not exist")),
+        _ => Ok(()),
+    }
+}
+
+/// Reads content from formats that only produce key/line pairs, where the
+/// version is declared as a key holding a single line.
+pub(crate) fn from_line_pairs(
+    mut line_pairs: LinePairs,
+    duplicates: Duplicates,
+) -> Result<Content, SchemaError> {
+    let schema_version = match line_pairs.remove(SCHEMA_VERSION_KEY) {
+        Some(lines) => {
+            let version = lines.iter().next().filter(|_| lines.len() == 1);
+            let version = version.and_then(|v| v.trim().parse().ok()).ok_or_else(|| {
+                error(Some(SCHEMA_VERSION_KEY), "expected a single version number")
+            })?;
+            Some(version)
+        }
+        None => None,
+    };
+    check_version(schema_version)?;
+    Ok(Content {
+        schema_version,
+        line_pairs,
+        line_meta: LineMetas::default(),
+        max_lengths: Vec::new(),
+        duplicates,
+    })
+}
+
+/// Reads content from a structured document (JSON, TOML and friends).
+///
+/// In `strict` mode, unknown line fields, negative or infinite weights,
+/// duplicate lines and includes that aren't paths are errors too.
+pub(crate) fn from_value(value: Value, strict: bool) -> Result<Content, SchemaError> {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => return Err(error(None, "expected a map of keys to lines")),
+    };
+
+    let mut content = Content::default();
+    if let Some(version) = map.get(SCHEMA_VERSION_KEY) {
+        let version = version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| error(Some(SCHEMA_VERSION_KEY), "expected a version number"))?;
+        content.schema_version = Some(version);
+    }
+    check_version(content.schema_version)?;
+    let version = content.schema_version.unwrap_or(1);
+
+    for (key, value) in map {
+        if key == SCHEMA_VERSION_KEY {
+            continue;
+        }
+        if key == MAX_LENGTH_KEY {
+            content.max_lengths = max_lengths(value)?;
+            continue;
+        }
+        let (values, listed) = match value {
+            Value::Array(values) => (values, true),
+            value => (vec![value], false),
+        };
+        let mut lines = HashSet::new();
+        for (i, value) in values.into_iter().enumerate() {
+            let path = if listed {
+                format!("[{}]", i)
+            } else {
+                String::new()
+            };
+            let line = match value {
+                Value::String(line) => line,
+                Value::Object(_) if strict && key == INCLUDE_KEY => {
+                    return Err(error_at(&key, path, "expected a path"))
+                }
+                Value::Object(object) if version >= 2 => {
+                    let (line, meta) = line_object(&key, &path, object, strict)?;
+                    content
+                        .line_meta
+                        .entry(key.clone())
+                        .or_default()
+                        .insert(line.clone(), meta);
+                    line
+                }
+                Value::Object(_) => {
+                    return Err(error_at(
+                        &key,
+                        path,
+                        "lines with metadata require schema_version 2",
+                    ))
+                }
+                other => {
+                    return Err(error_at(
+                        &key,
+                        path,
+                        format!("expected a line, found `{}`", other),
+                    ))
+                }
+            };
+            if !lines.insert(line.clone()) {
+                if strict {
+                    return Err(error_at(&key, path, "duplicate line"));
+                }
+                content.duplicates.push((key.clone(), line));
+            }
+        }
+        content.line_pairs.insert(key, lines);
+    }
+    Ok(content)
+}
+
+/// Reads a map of key patterns to maximum lengths.
+fn max_lengths(value: Value) -> Result<Vec<(String, usize)>, SchemaError> {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => {
+            return Err(error(
+                Some(MAX_LENGTH_KEY),
+                "expected a map of keys to lengths",
+            ))
+        }
+    };
+    map.into_iter()
+        .map(|(pattern, max)| match max.as_u64() {
+            Some(max) => Ok((pattern, max as usize)),
+            None => Err(error_at(
+                MAX_LENGTH_KEY,
+                format!(".{}", pattern),
+                "expected a length",
+            )),
+        })
+        .collect()
+}
+
+/// Reads a `{ text, weight, tags, id }` line object at `path` within `key`.
+fn line_object(
+    key: &str,
+    path: &str,
+    mut object: serde_json::Map<String, Value>,
+    strict: bool,
+) -> Result<(String, LineMeta), SchemaError> {
+    let field = |name: &str| format!("{}.{}", path, name);
+    let text = match object.remove("text") {
+        Some(Value::String(text)) => text,
+        Some(_) => return Err(error_at(key, field("text"), "`text` must be a string")),
+        None => {
+            return Err(error_at(
+                key,
+                path.to_string(),
+                "line objects need a `text` string",
+            ))
+        }
+    };
+    let mut meta = LineMeta::default();
+    if let Some(weight) = object.remove("weight") {
+        meta.weight = weight
+            .as_f64()
+            .ok_or_else(|| error_at(key, field("weight"), "`weight` must be a number"))?;
+        if strict && !(meta.weight.is_finite() && meta.weight >= 0.0) {
+            return Err(error_at(
+                key,
+                field("weight"),
+                "`weight` must be zero or more",
+            ));
+        }
+    }
+    if let Some(tags) = object.remove("tags") {
+        meta.tags = match tags {
+            Value::Array(tags) => tags
+                .into_iter()
+                .enumerate()
+                .map(|(i, tag)| match tag {
+                    Value::String(tag) => Ok(tag),
+                    _ => Err(error_at(
+                        key,
+                        format!("{}[{}]", field("tags"), i),
+                        "`tags` must be strings",
+                    )),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(error_at(key, field("tags"), "`tags` must be a list")),
+        };
+    }
+    if let Some(id) = object.remove("id") {
+        meta.id = match id {
+            Value::String(id) => Some(id),
+            _ => return Err(error_at(key, field("id"), "`id` must be a string")),
+        };
+    }
+    if strict {
+        if let Some(name) = object.keys().next() {
+            return Err(error_at(
+                key,
+                field(name),
+                format!(
+                    "unknown field `{}`, expected text, weight, tags or id",
+                    name
+                ),
+            ));
+        }
+    }
+    Ok((text, meta))
+}
+
+/// A [JSON Schema](https://json-schema.org) describing content files of the
+/// current schema version, for editors to validate and complete content
+/// files with, e.g. written to `lines.schema.json` and referenced from
+/// JSON's `$schema` or an editor's schema settings.
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "hottext content",
+        "description": "A map of keys to the lines drawn for them.",
+        "type": "object",
+        "definitions": {
+            "line": {
+                "oneOf": [
+                    { "type": "string" },
+                    {
+                        "type": "object",
+                        "description": "A line with metadata, requires schema_version 2.",
+                        "properties": {
+                            "text": { "type": "string" },
+                            "weight": {
+                                "type": "number",
+                                "description": "Relative likelihood of the line being chosen.",
+                                "default": 1.0,
+                            },
+                            "tags": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "uniqueItems": true,
+                            },
+                            "id": {
+                                "type": "string",
+                                "description": "Authored ID of the line.",
+                            },
+                        },
+                        "required": ["text"],
+                        "additionalProperties": false,
+                    },
+                ],
+            },
+        },
+        "properties": {
+            SCHEMA_VERSION_KEY: {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": SCHEMA_VERSION,
+                "description": "The schema version the file is written in.",
+            },
+            MAX_LENGTH_KEY: {
+                "description": "Maximum rendered lengths of lines, by key or pattern with `*` and `?` wildcards.",
+                "type": "object",
+                "additionalProperties": { "type": "integer", "minimum": 0 },
+            },
+            INCLUDE_KEY: {
+                "description": "Files to include, relative to this one, with `*` and `?` wildcards.",
+                "oneOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" } },
+                ],
+            },
+        },
+        "additionalProperties": {
+            "oneOf": [
+                { "$ref": "#/definitions/line" },
+                { "type": "array", "items": { "$ref": "#/definitions/line" } },
+            ],
+        },
+    })
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets whether content files are validated strictly while loading, so
+    /// mistakes like misspelled line fields (`wieght = 0.1`), negative
+    /// weights and duplicate lines are errors instead of being ignored.
+    /// Errors name the offending value, e.g. `combat.encounter[1].wieght`.
+    ///
+    /// Only JSON, TOML and the other formats with line objects are checked.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Chainable variant of [`HotText::set_strict()`]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.set_strict(strict);
+        self
+    }
+
+    /// Stores line metadata read from a file, for lines that were kept.
+    pub(crate) fn load_meta(&mut self, line_meta: LineMetas) {
+        for (key, lines) in line_meta {
+            let key = self.normalize_key(&key).into_owned();
+            for (line, meta) in lines {
+                let line = self.normalize_line(&line);
+                if self.line_pairs.get(&key).is_some_and(|l| l.contains(&line)) {
+                    self.line_meta
+                        .entry(key.clone())
+                        .or_default()
+                        .insert(line, meta);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_two() {
+        let value: Value = toml::from_str::<toml::Value>(
+            "schema_version = 2\n\
+             \"combat.encounter\" = [\n\
+             \x20   \"A lion!\",\n\
+             \x20   { text = \"A dragon!\", weight = 0.1, tags = [\"rare\"], id = \"dragon\" },\n\
+             ]\n",
+        )
+        .map(|v| serde_json::to_value(v).unwrap())
+        .unwrap();
+        let content = from_value(value, false).unwrap();
+
+        assert_eq!(content.schema_version, Some(2));
+        assert_eq!(content.line_pairs["combat.encounter"].len(), 2);
+        let meta = &content.line_meta["combat.encounter"]["A dragon!"];
+        assert_eq!(meta.weight, 0.1);
+        assert!(meta.tags.contains("rare"));
+        assert_eq!(meta.id.as_deref(), Some("dragon"));
+    }
+
+    #[test]
+    fn version_one() {
+        let content = from_value(
+            serde_json::json!({ "greet": ["Hi!", "Hello!"], "bye": "Bye!" }),
+            false,
+        )
+        .unwrap();
+        assert_eq!(content.schema_version, None);
+        assert_eq!(content.line_pairs["bye"].len(), 1);
+
+        assert!(from_value(serde_json::json!({ "greet": [{ "text": "Hi!" }] }), false).is_err());
+        assert!(from_value(serde_json::json!({ "schema_version": 99 }), false).is_err());
+        assert!(from_value(serde_json::json!({ "greet": [1] }), false).is_err());
+    }
+
+    #[test]
+    fn reject_out_of_range_versions() {
+        // 2^32 + 2 would truncate to a supported version 2.
+        for version in [json!(4_294_967_298_u64), json!(-1)] {
+            let error = from_value(json!({ "schema_version": version }), false).unwrap_err();
+            assert_eq!(error.key.as_deref(), Some(SCHEMA_VERSION_KEY));
+        }
+    }
+
+    #[test]
+    fn describe_schema() {
+        let schema = json_schema();
+        assert_eq!(
+            schema["properties"][SCHEMA_VERSION_KEY]["maximum"],
+            SCHEMA_VERSION
+        );
+        let line = &schema["definitions"]["line"]["oneOf"][1];
+        assert_eq!(line["required"], json!(["text"]));
+        for field in &["text", "weight", "tags", "id"] {
+            assert!(line["properties"].get(*field).is_some());
+        }
+    }
+
+    #[test]
+    fn strict_validation() {
+        let value = serde_json::json!({
+            "schema_version": 2,
+            "combat.encounter": ["A lion!", { "text": "A dragon!", "wieght": 0.1 }],
+        });
+        assert!(from_value(value.clone(), false).is_ok());
+        let error = from_value(value, true).unwrap_err();
+        assert_eq!(error.path.as_deref(), Some("[1].wieght"));
+        assert!(error
+            .to_string()
+            .starts_with("SchemaError in `combat.encounter[1].wieght`"));
+
+        let strict = |value| {
+            from_value(value, true)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        };
+        assert!(strict(serde_json::json!({ "greet": ["Hi!", "Hi!"] })).is_err());
+        assert!(strict(serde_json::json!({
+            "schema_version": 2,
+            "greet": [{ "text": "Hi!", "weight": -1 }],
+        }))
+        .is_err());
+        assert!(strict(serde_json::json!({ "greet": ["Hi!", "Hello!"] })).is_ok());
+    }
+
+    #[test]
+    fn version_key_in_line_pairs() {
+        let mut line_pairs = LinePairs::default();
+        line_pairs.insert(
+            SCHEMA_VERSION_KEY.to_string(),
+            vec!["2".to_string()].into_iter().collect(),
+        );
+        assert_eq!(
+            from_line_pairs(line_pairs, Vec::new())
+                .unwrap()
+                .schema_version,
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Searching the loaded content by key or line, for editor tooling.
+//!
+//! Patterns are matched against the whole key or line, where `*` matches any
+//! run of characters and `?` any single character, e.g. `*old king*`.
+
+use rand::Rng;
+
+use crate::{include, HotText};
+
+impl<R: Rng> HotText<R> {
+    /// Gets the keys of the base content and enabled layers matching `pattern`, sorted.
+    pub fn find_keys(&self, pattern: &str) -> Vec<&str> {
+        self.drawable_keys()
+            .into_iter()
+            .filter(|key| include::wildcard_match(pattern, key))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Gets the key and line of every line matching `pattern`, sorted by key
+    /// and line, e.g. `find_lines("*old king*")` to find where he's mentioned.
+    pub fn find_lines(&self, pattern: &str) -> Vec<(&str, &str)> {
+        let mut found: Vec<(&str, &str)> = std::iter::once(&*self.line_pairs)
+            .chain(
+                self.layers
+                    .iter()
+                    .filter(|layer| layer.enabled)
+                    .map(|layer| &layer.line_pairs),
+            )
+            .flat_map(|line_pairs| {
+                line_pairs
+                    .iter()
+                    .flat_map(|(key, lines)| lines.iter().map(move |line| (key, line)))
+            })
+            .filter(|(_, line)| include::wildcard_match(pattern, line))
+            .map(|(key, line)| (key.as_str(), line.as_str()))
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_content() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("rumors.king", "The old king is ill.").unwrap();
+        ht.insert("rumors.dragon", "A dragon was seen.").unwrap();
+        ht.insert("greet", "Long live the old king!").unwrap();
+
+        assert_eq!(
+            ht.find_keys("rumors.*"),
+            vec!["rumors.dragon", "rumors.king"]
+        );
+        assert_eq!(
+            ht.find_lines("*old king*"),
+            vec![
+                ("greet", "Long live the old king!"),
+                ("rumors.king", "The old king is ill.")
+            ]
+        );
+        assert!(ht.find_lines("old king").is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+//! Deterministic line selection, for snapshot tests of UI text and stable
+//! per-entity variants.
+
+use rand::Rng;
+
+use crate::{hash, layer, telemetry, HotText};
+
+/// How a line is picked from the lines of a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// At random, according to each line's weight.
+    #[default]
+    Random,
+    /// Always the lexicographically first line.
+    First,
+    /// The line at this index in lexicographic order, wrapping around.
+    Index(usize),
+}
+
+impl Selection {
+    /// Picks a candidate deterministically, or returns `None` for [`Selection::Random`]
+    /// or when there are no candidates. As with random draws, candidates
+    /// without weight are never picked.
+    pub(crate) fn pick<T, K: Ord, F: Fn(&T) -> K, W: Fn(&T) -> f64>(
+        self,
+        candidates: &[T],
+        sort_key: F,
+        weight: W,
+    ) -> Option<&T> {
+        let mut sorted: Vec<&T> = candidates
+            .iter()
+            .filter(|candidate| weight(candidate) > 0.0)
+            .collect();
+        let index = match self {
+            Selection::Random => return None,
+            Selection::First => 0,
+            Selection::Index(index) => index % sorted.len().max(1),
+        };
+        sorted.sort_by_key(|candidate| sort_key(candidate));
+        sorted.get(index).copied()
+    }
+}
+
+/// Picks an item according to its weight, using `hash` in place of a random
+/// number, so the same hash always picks the same item.
+pub(crate) fn pick_hashed<T>(items: &[T], weight: impl Fn(&T) -> f64, hash: u64) -> Option<&T> {
+    // SplitMix64's finalizer, so similar inputs still spread evenly.
+    let mut hash = hash;
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^= hash >> 31;
+
+    let total: f64 = items.iter().map(&weight).filter(|w| *w > 0.0).sum();
+    let mut point = (hash >> 11) as f64 / (1u64 << 53) as f64 * total;
+    let mut last = None;
+    for item in items.iter().filter(|item| weight(item) > 0.0) {
+        if point < weight(item) {
+            return Some(item);
+        }
+        point -= weight(item);
+        last = Some(item);
+    }
+    last
+}
+
+impl<R: Rng> HotText<R> {
+    /// Sets how lines are picked. With anything but [`Selection::Random`],
+    /// draws don't depend on the RNG, so tests of rendered text don't flake.
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = selection;
+    }
+
+    /// Chainable variant of [`HotText::set_selection()`]
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.set_selection(selection);
+        self
+    }
+
+    /// Sets how lines of one key are picked, or falls back to the collection's
+    /// selection with `None`.
+    pub fn set_key_selection(&mut self, key: &str, selection: Option<Selection>) {
+        let key = self.normalize_key(key).into_owned();
+        match selection {
+            Some(selection) => self.key_selections.insert(key, selection),
+            None => self.key_selections.remove(&key),
+        };
+    }
+
+    /// Chainable variant of [`HotText::set_key_selection()`]
+    pub fn with_key_selection(mut self, key: &str, selection: Selection) -> Self {
+        self.set_key_selection(key, Some(selection));
+        self
+    }
+
+    /// Gets the line of a key assigned to `entity_id`, e.g. an NPC's ID, so the
+    /// same entity always gets the same variant while different entities vary.
+    ///
+    /// Lines are assigned according to their weights, and assignments stay the
+    /// same across sessions as long as the key's lines don't change. The RNG
+    /// and cooldowns aren't used.
+    pub fn get_line_for(&mut self, key: &str, entity_id: &str) -> Option<String> {
+        let resolved = self.resolve_key(key);
+        let resolved_key = resolved.as_deref().unwrap_or(key);
+        let candidates = layer::candidates(
+            &self.line_pairs,
+            &self.line_meta,
+            &self.layers,
+            resolved_key,
+        );
+        let hash = hash::fnv1a(format!("{}\0{}", resolved_key, entity_id).as_bytes());
+        let line =
+            pick_hashed(&candidates, |(_, weight)| *weight, hash).map(|(line, _)| (*line).clone());
+        match &line {
+            Some(line) => {
+                self.record_draw(resolved_key, line);
+                self.notify_draw(resolved_key, line);
+            }
+            None => self.record_miss(key, telemetry::MissKind::MissingKey, &[]),
+        }
+        line
+    }
+
+    pub(crate) fn selection_for(&self, key: &str) -> Selection {
+        self.key_selections
+            .get(key)
+            .copied()
+            .unwrap_or(self.selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_selection() {
+        let mut ht = HotText::new(rand::thread_rng()).with_selection(Selection::First);
+        for line in &["Howdy!", "Hello!", "Hi!"] {
+            ht.insert("greet", line).unwrap();
+        }
+        ht.insert("greet.formal", "Good day.").unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+            assert_eq!(ht.get_line_matching("greet*").unwrap(), "Good day.");
+        }
+        ht.set_selection(Selection::Index(2));
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Howdy!");
+        assert_eq!(ht.get_line_matching("greet*").unwrap(), "Hi!");
+
+        ht.set_key_selection("greet", Some(Selection::First));
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        assert_eq!(ht.get_line_raw("greet.formal").unwrap(), "Good day.");
+
+        let never = crate::LineMeta {
+            weight: 0.0,
+            ..Default::default()
+        };
+        ht.insert_with_meta("greet", "Ahoy!", never).unwrap();
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+        ht.set_key_selection("greet", Some(Selection::Index(3)));
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hello!");
+    }
+
+    #[test]
+    fn per_entity_selection() {
+        let mut ht = HotText::new(rand::thread_rng());
+        for line in &["Howdy!", "Hello!", "Hi!"] {
+            ht.insert("greet", line).unwrap();
+        }
+        ht.insert_with_meta(
+            "greet",
+            "Never mind.",
+            crate::LineMeta {
+                weight: 0.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let npcs: Vec<String> = (0..30).map(|i| format!("npc_{}", i)).collect();
+        let lines: Vec<String> = npcs
+            .iter()
+            .map(|npc| ht.get_line_for("greet", npc).unwrap())
+            .collect();
+        for (npc, line) in npcs.iter().zip(&lines) {
+            assert_eq!(&ht.get_line_for("greet", npc).unwrap(), line);
+        }
+        for line in &["Howdy!", "Hello!", "Hi!"] {
+            assert!(lines.iter().any(|l| l == line));
+        }
+        assert!(!lines.iter().any(|l| l == "Never mind."));
+        assert!(ht.get_line_for("missing", "npc_1").is_none());
+    }
+}
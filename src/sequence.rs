@@ -0,0 +1,134 @@
+//! Keys whose lines are ordered steps rather than interchangeable variants.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::HotText;
+
+/// How far along each sequence a player, NPC or session is.
+///
+/// [`HotText`] keeps one of these for [`HotText::next_line()`]; keep your own
+/// per instance with [`HotText::next_line_with()`]. It can be serialized to
+/// save progress.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceState {
+    positions: HashMap<String, usize>,
+}
+
+impl SequenceState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of steps taken through the sequence `key`.
+    pub fn position(&self, key: &str) -> usize {
+        self.positions.get(key).copied().unwrap_or(0)
+    }
+
+    /// Starts the sequence `key` over.
+    pub fn reset(&mut self, key: &str) {
+        self.positions.remove(key);
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Inserts the lines of a sequence, in order.
+    ///
+    /// The lines are added under `key` like any other, and are also stepped
+    /// through in order by [`HotText::next_line()`]. A write-through file is
+    /// written once for the whole sequence.
+    pub fn insert_sequence(&mut self, key: &str, steps: &[&str]) -> Result<(), Box<dyn Error>> {
+        let steps: Vec<String> = steps.iter().map(|step| self.normalize_line(step)).collect();
+        self.batch(|ht| {
+            for step in &steps {
+                ht.insert(key, step)?;
+            }
+            let key = ht.normalize_key(key).into_owned();
+            ht.sequences.insert(key, steps);
+            Ok(())
+        })
+    }
+
+    /// Chainable variant of [`HotText::insert_sequence()`]
+    pub fn with_sequence(mut self, key: &str, steps: &[&str]) -> Result<Self, Box<dyn Error>> {
+        self.insert_sequence(key, steps)?;
+        Ok(self)
+    }
+
+    /// Gets the next step of the sequence `key`, staying on the last step once
+    /// it is reached.
+    pub fn next_line(&mut self, key: &str) -> Option<String> {
+        let mut state = std::mem::take(&mut self.sequence_state);
+        let line = self.next_line_with(key, &mut state);
+        self.sequence_state = state;
+        line
+    }
+
+    /// Variant of [`HotText::next_line()`] that advances the provided state.
+    pub fn next_line_with(&self, key: &str, state: &mut SequenceState) -> Option<String> {
+        let key = self.normalize_key(key);
+        let steps = self.sequences.get(key.as_ref())?;
+        let position = state.position(&key);
+        let line = steps.get(position.min(steps.len().checked_sub(1)?))?;
+        state
+            .positions
+            .insert(key.into_owned(), (position + 1).min(steps.len()));
+        Some(line.clone())
+    }
+
+    /// Gets the state advanced by [`HotText::next_line()`].
+    pub fn sequence_state(&self) -> &SequenceState {
+        &self.sequence_state
+    }
+
+    /// Replaces the state advanced by [`HotText::next_line()`], e.g. with one
+    /// restored from a save.
+    pub fn set_sequence_state(&mut self, state: SequenceState) {
+        self.sequence_state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_lines;
+
+    #[test]
+    fn step_through() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_sequence("npc.annoyed", &["Hello.", "Yes?", "Go away!"])
+            .unwrap();
+
+        assert_eq!(ht.next_line("npc.annoyed").unwrap(), "Hello.");
+        assert_eq!(ht.next_line("npc.annoyed").unwrap(), "Yes?");
+
+        let mut other = SequenceState::new();
+        assert_eq!(
+            ht.next_line_with("npc.annoyed", &mut other).unwrap(),
+            "Hello."
+        );
+
+        assert_eq!(ht.next_line("npc.annoyed").unwrap(), "Go away!");
+        assert_eq!(ht.next_line("npc.annoyed").unwrap(), "Go away!");
+        assert_eq!(get_lines!(ht, "npc.annoyed").len(), 3);
+        assert!(ht.next_line("missing").is_none());
+    }
+
+    #[test]
+    fn serialize_state() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_sequence("tutorial", &["Move.", "Jump.", "Fight."])
+            .unwrap();
+        ht.next_line("tutorial");
+
+        let saved = serde_json::to_string(ht.sequence_state()).unwrap();
+        ht.set_sequence_state(SequenceState::new());
+        assert_eq!(ht.next_line("tutorial").unwrap(), "Move.");
+
+        ht.set_sequence_state(serde_json::from_str(&saved).unwrap());
+        assert_eq!(ht.next_line("tutorial").unwrap(), "Jump.");
+    }
+}
@@ -0,0 +1,465 @@
+//! An embeddable HTTP endpoint for editing lines in a running game.
+//!
+//! The server never owns the collection: the game calls
+//! [`EditServer::poll()`] with it, e.g. once per frame, and requests are
+//! handled right there. Routes:
+//!
+//! - `GET /keys`: every key, as a JSON array.
+//! - `GET /lines?key=combat.taunt`: the lines of a key, as a JSON array.
+//! - `GET /render?key=greet&name=Brom`: renders a line, with the other query
+//!   parameters as data.
+//! - `POST /lines?key=greet`: adds the request body as a line.
+//! - `PUT /lines?key=greet&line=Hi!`: replaces a line with the request body.
+//! - `DELETE /lines?key=greet&line=Hi!`: removes a line.
+//!
+//! Requests that edit lines must send the server's
+//! [token](EditServer::token()) as `Authorization: Bearer <token>`, so web
+//! pages open in the same browser can't edit content. Reads need no token,
+//! but still only bind to addresses that playtesters and writers can reach.
+
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// The time one poll may spend reading requests, shared by all of them.
+const TIMEOUT: Duration = Duration::from_millis(20);
+/// The most connections handled per poll; the rest wait for the next one.
+const MAX_CONNECTIONS: usize = 16;
+const MAX_HEADER: u64 = 8 << 10;
+const MAX_BODY: usize = 1 << 20;
+
+/// A listener for edit requests, handled whenever it is polled.
+#[derive(Debug)]
+pub struct EditServer {
+    listener: TcpListener,
+    token: String,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    /// The bearer token of the `Authorization` header.
+    token: Option<String>,
+    body: String,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json<T: serde::Serialize>(value: &T) -> Self {
+        Response {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into(),
+        }
+    }
+}
+
+/// Decodes `%XX` escapes and `+` for spaces in a URL component.
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' if tail.len() >= 2 => {
+                match u8::from_str_radix(&String::from_utf8_lossy(&tail[..2]), 16) {
+                    Ok(decoded) => {
+                        bytes.push(decoded);
+                        rest = &tail[2..];
+                        continue;
+                    }
+                    Err(_) => bytes.push(b'%'),
+                }
+            }
+            byte => bytes.push(byte),
+        }
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Reads from a stream until a deadline for the whole request, so clients
+/// trickling bytes can't hold up the game.
+struct DeadlineReader<'a> {
+    stream: &'a mut TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "request timed out"))?;
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn read_request(stream: &mut TcpStream, deadline: Instant) -> Result<Request, Box<dyn Error>> {
+    let mut reader = BufReader::new(DeadlineReader { stream, deadline });
+    let mut head = (&mut reader).take(MAX_HEADER);
+    let mut request_line = String::new();
+    read_head_line(&mut head, &mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or("empty request")?.to_string();
+    let target = parts.next().ok_or("request without a path")?;
+
+    let mut content_length = 0;
+    let mut token = None;
+    loop {
+        let mut header = String::new();
+        if read_head_line(&mut head, &mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            } else if name.trim().eq_ignore_ascii_case("authorization") {
+                token = value.trim().strip_prefix("Bearer ").map(str::to_string);
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err("request body too large".into());
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        token,
+        body: String::from_utf8(body)?,
+    })
+}
+
+/// Reads one line of the request line and headers, which may take up to
+/// [`MAX_HEADER`] bytes together.
+fn read_head_line(head: &mut impl BufRead, line: &mut String) -> Result<usize, Box<dyn Error>> {
+    let read = head.read_line(line)?;
+    if read > 0 && !line.ends_with('\n') {
+        return Err("request head too large".into());
+    }
+    Ok(read)
+}
+
+/// Compares tokens in time that doesn't depend on where they differ.
+fn tokens_match(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |differ, (a, b)| differ | (a ^ b))
+            == 0
+}
+
+impl EditServer {
+    /// Starts listening on `address`, e.g. `127.0.0.1:7878`, with a new
+    /// random token.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let token = (0..4)
+            .map(|_| format!("{:08x}", rand::random::<u32>()))
+            .collect();
+        Ok(EditServer { listener, token })
+    }
+
+    /// Gets the token that requests editing lines must send, e.g. to show to
+    /// playtesters or pass to editing tools.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Gets the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handles the requests waiting to be accepted against `ht`, returning
+    /// how many were handled. Never blocks when no request is waiting, and
+    /// spends at most a few milliseconds reading requests, leaving any others
+    /// for the next poll.
+    pub fn poll<R: Rng>(&self, ht: &mut HotText<R>) -> io::Result<usize> {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut handled = 0;
+        while handled < MAX_CONNECTIONS && Instant::now() < deadline {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(handled),
+                Err(error) => return Err(error),
+            };
+            stream.set_nonblocking(false)?;
+            let response = match read_request(&mut stream, deadline) {
+                Ok(request)
+                    if is_edit(&request)
+                        && !request
+                            .token
+                            .as_deref()
+                            .is_some_and(|token| tokens_match(token, &self.token)) =>
+                {
+                    Response::text(401, "missing or wrong token")
+                }
+                Ok(request) => respond(ht, &request),
+                Err(error) => Response::text(400, error.to_string()),
+            };
+            let head = format!(
+                "HTTP/1.0 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                response.status,
+                reason(response.status),
+                response.content_type,
+                response.body.len()
+            );
+            // A client that hung up doesn't stop the others from being served.
+            let _ = stream
+                .write_all(head.as_bytes())
+                .and_then(|_| stream.write_all(response.body.as_bytes()));
+            handled += 1;
+        }
+        Ok(handled)
+    }
+}
+
+/// Whether `request` would change the lines.
+fn is_edit(request: &Request) -> bool {
+    request.method != "GET"
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    }
+}
+
+fn respond<R: Rng>(ht: &mut HotText<R>, request: &Request) -> Response {
+    let key = request.param("key");
+    match (request.method.as_str(), request.path.as_str(), key) {
+        ("GET", "/keys", _) => Response::json(&ht.drawable_keys()),
+        ("GET", "/lines", Some(key)) => match ht.get_lines_raw(key) {
+            Some(lines) => {
+                let mut lines: Vec<String> = lines.into_iter().collect();
+                lines.sort();
+                Response::json(&lines)
+            }
+            None => Response::text(404, format!("no lines for `{}`", key)),
+        },
+        ("GET", "/render", Some(key)) => {
+            let data: Vec<(&str, &str)> = request
+                .query
+                .iter()
+                .filter(|(name, _)| name != "key")
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            match ht.render_line(key, data) {
+                Ok(line) => Response::text(200, line),
+                Err(error) => Response::text(400, error.to_string()),
+            }
+        }
+        ("POST", "/lines", Some(key)) => match ht.insert(key, &request.body) {
+            Ok(()) => Response::text(201, ""),
+            Err(error) => Response::text(400, error.to_string()),
+        },
+        ("PUT", "/lines", Some(key)) | ("DELETE", "/lines", Some(key)) => {
+            let line = match request.param("line") {
+                Some(line) => line,
+                None => return Response::text(400, "missing `line` parameter"),
+            };
+            let body = &request.body;
+            let put = request.method == "PUT";
+            let result = ht.transaction(|tx| {
+                if !tx.remove(key, line)? {
+                    return Ok(false);
+                }
+                if put {
+                    tx.insert(key, body)?;
+                }
+                Ok(true)
+            });
+            match result {
+                Ok(true) => Response::text(200, ""),
+                Ok(false) => Response::text(404, format!("no line `{}` in `{}`", line, key)),
+                Err(error) => Response::text(400, error.to_string()),
+            }
+        }
+        (_, "/keys", _) | (_, "/lines", Some(_)) | (_, "/render", Some(_)) => {
+            Response::text(405, "method not allowed")
+        }
+        (_, "/lines", None) | (_, "/render", None) => {
+            Response::text(400, "missing `key` parameter")
+        }
+        _ => Response::text(404, "not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn send(address: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn edit_over_http() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hi, {{name}}!").unwrap();
+        let server = EditServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let auth = format!("Authorization: Bearer {}", server.token());
+        assert_eq!(server.poll(&mut ht).unwrap(), 0);
+
+        let client = thread::spawn(move || {
+            vec![
+                send(address, "GET /keys HTTP/1.0\r\n\r\n"),
+                send(address, "GET /render?key=greet&name=Brom+Stone HTTP/1.0\r\n\r\n"),
+                send(
+                    address,
+                    &format!("PUT /lines?key=greet&line=Hi%2C%20%7B%7Bname%7D%7D! HTTP/1.0\r\n{}\r\nContent-Length: 14\r\n\r\nHey, {{{{name}}}}!", auth),
+                ),
+                send(
+                    address,
+                    &format!("POST /lines?key=bye HTTP/1.0\r\n{}\r\nContent-Length: 4\r\n\r\nBye!", auth),
+                ),
+                send(
+                    address,
+                    &format!("DELETE /lines?key=bye&line=Nope HTTP/1.0\r\n{}\r\n\r\n", auth),
+                ),
+                send(address, "GET /lines HTTP/1.0\r\n\r\n"),
+                send(
+                    address,
+                    "POST /lines?key=bye HTTP/1.0\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nPwned",
+                ),
+            ]
+        });
+        while !client.is_finished() {
+            server.poll(&mut ht).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        let responses = client.join().unwrap();
+
+        assert!(responses[0].ends_with("[\"greet\"]"));
+        assert!(responses[1].ends_with("\r\n\r\nHi, Brom Stone!"));
+        assert!(responses[2].starts_with("HTTP/1.0 200"));
+        assert!(responses[3].starts_with("HTTP/1.0 201"));
+        assert!(responses[4].starts_with("HTTP/1.0 404"));
+        assert!(responses[5].starts_with("HTTP/1.0 400"));
+        assert!(responses[6].starts_with("HTTP/1.0 401"));
+        assert!(responses.iter().all(|r| !r.contains("Access-Control")));
+        assert_eq!(ht.get_lines_raw("bye").unwrap().len(), 1);
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hey, {{name}}!");
+        assert_eq!(ht.get_line_raw("bye").unwrap(), "Bye!");
+    }
+
+    #[test]
+    fn request_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            // Each byte arrives well within a read timeout, but never a full request.
+            for _ in 0..20 {
+                if stream.write_all(b"G").is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+        let (mut stream, _) = listener.accept().unwrap();
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        assert!(read_request(&mut stream, deadline).is_err());
+        assert!(start.elapsed() < Duration::from_millis(300));
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn limit_requests() {
+        let mut ht = HotText::new(rand::thread_rng());
+        let server = EditServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+
+        let streams: Vec<TcpStream> = (0..MAX_CONNECTIONS + 4)
+            .map(|_| {
+                let mut stream = TcpStream::connect(address).unwrap();
+                stream.write_all(b"GET /keys HTTP/1.0\r\n\r\n").unwrap();
+                stream
+            })
+            .collect();
+        let mut handled = 0;
+        while handled < streams.len() {
+            let polled = server.poll(&mut ht).unwrap();
+            assert!(polled <= MAX_CONNECTIONS);
+            handled += polled;
+        }
+
+        let mut client = TcpStream::connect(address).unwrap();
+        let header = format!("X-Padding: {}\r\n", "a".repeat(MAX_HEADER as usize));
+        client
+            .write_all(format!("GET /keys HTTP/1.0\r\n{}\r\n", header).as_bytes())
+            .unwrap();
+        let (mut stream, _) = loop {
+            match server.listener.accept() {
+                Ok(accepted) => break accepted,
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        stream.set_nonblocking(false).unwrap();
+        let error = read_request(&mut stream, Instant::now() + Duration::from_secs(1));
+        assert_eq!(error.err().unwrap().to_string(), "request head too large");
+
+        assert!(tokens_match("0123abcd", "0123abcd"));
+        assert!(!tokens_match("0123abce", "0123abcd"));
+        assert!(!tokens_match("0123abc", "0123abcd"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Keeping draws stable within a context, like one visit to a shop.
+
+use std::collections::HashMap;
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+#[cfg(feature = "templates")]
+use crate::RenderOptions;
+use crate::{HotText, MissKind};
+
+/// The line drawn for each key, per context.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SessionCache {
+    contexts: HashMap<String, HashMap<String, String>>,
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets one line of a key, drawn the first time the key is requested for
+    /// `context_id` and returned again on later calls until the context is
+    /// [invalidated](HotText::invalidate_context()), e.g. so a shopkeeper's
+    /// greeting stays the same within a visit but varies between visits.
+    pub fn get_line_cached(&mut self, key: &str, context_id: &str) -> Option<String> {
+        let line = self.draw_cached(key, context_id);
+        if line.is_none() {
+            self.record_miss(key, MissKind::MissingKey, &[]);
+        }
+        line
+    }
+
+    /// Variant of [`HotText::get_line_cached()`] that doesn't log a miss.
+    fn draw_cached(&mut self, key: &str, context_id: &str) -> Option<String> {
+        if let Some(line) = self
+            .session_cache
+            .contexts
+            .get(context_id)
+            .and_then(|lines| lines.get(key))
+        {
+            return Some(line.clone());
+        }
+        let line = self.draw_line(key)?;
+        self.session_cache
+            .contexts
+            .entry(context_id.to_string())
+            .or_default()
+            .insert(key.to_string(), line.clone());
+        Some(line)
+    }
+
+    /// Variant of [`HotText::render_line()`] that renders the line returned by
+    /// [`HotText::get_line_cached()`].
+    #[cfg(feature = "templates")]
+    pub fn render_line_cached<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        context_id: &str,
+        data: D,
+    ) -> Result<String, Box<dyn Error>> {
+        let data: Vec<(&str, &str)> = data.into_iter().collect();
+        let raw_line = self.draw_cached(key, context_id);
+        self.render_inner(key, raw_line, &data, RenderOptions::default())
+    }
+
+    /// Forgets the lines drawn for `context_id`, so the next calls draw anew.
+    pub fn invalidate_context(&mut self, context_id: &str) {
+        self.session_cache.contexts.remove(context_id);
+    }
+
+    /// Forgets the lines drawn for every context.
+    pub fn clear_contexts(&mut self) {
+        self.session_cache.contexts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_per_context() {
+        let mut ht = HotText::new(rand::thread_rng());
+        for i in 0..20 {
+            ht.insert("shop.greet", &format!("Welcome, {{{{name}}}} #{}!", i))
+                .unwrap();
+        }
+
+        let first = ht.get_line_cached("shop.greet", "visit-1").unwrap();
+        for _ in 0..10 {
+            assert_eq!(ht.get_line_cached("shop.greet", "visit-1").unwrap(), first);
+        }
+        #[cfg(feature = "templates")]
+        assert_eq!(
+            ht.render_line_cached("shop.greet", "visit-1", vec![("name", "Brom")])
+                .unwrap(),
+            first.replace("{{name}}", "Brom")
+        );
+
+        let others: Vec<String> = (0..10)
+            .map(|i| {
+                ht.get_line_cached("shop.greet", &format!("visit-{}", i + 2))
+                    .unwrap()
+            })
+            .collect();
+        assert!(others.iter().any(|line| *line != first));
+
+        ht.invalidate_context("visit-1");
+        assert!(!ht.session_cache.contexts.contains_key("visit-1"));
+        ht.clear_contexts();
+        assert!(ht.get_line_cached("missing", "visit-1").is_none());
+    }
+}
@@ -0,0 +1,291 @@
+//! Sharing one collection between threads, with reloads that never block
+//! readers.
+//!
+//! Content is held as an immutable snapshot behind an [`Arc`]. A reload copies
+//! the current snapshot, applies its changes to the copy, and swaps the result
+//! in under a lock held only for the pointer exchange, bumping an epoch
+//! counter. Each reader draws from its own copy and only checks the epoch on
+//! the hot path, picking up the new snapshot once per reload. Copies share the
+//! snapshot's lines, metadata and layers, so only draw state such as history,
+//! cooldowns and sequence positions is copied.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use rand::Rng;
+
+use crate::HotText;
+
+#[derive(Debug)]
+struct Inner<R: Rng> {
+    current: Mutex<Arc<HotText<R>>>,
+    epoch: AtomicU64,
+    /// Held by reloads for the whole build so concurrent reloads don't drop
+    /// each other's changes. Readers never take it.
+    writer: Mutex<()>,
+}
+
+/// A handle to a collection shared between threads. Cloning the handle shares
+/// the same content.
+///
+/// ```
+/// # use hottext::{HotText, SharedHotText};
+/// # use rand::{rngs::StdRng, SeedableRng};
+/// let shared = SharedHotText::new(HotText::new(StdRng::seed_from_u64(0)));
+/// let mut reader = shared.reader(StdRng::seed_from_u64(1));
+///
+/// shared
+///     .reload(|ht| ht.insert("greet", "Hello!"))
+///     .unwrap();
+/// assert_eq!(reader.latest().get_line_raw("greet").unwrap(), "Hello!");
+/// ```
+#[derive(Debug)]
+pub struct SharedHotText<R: Rng> {
+    inner: Arc<Inner<R>>,
+}
+
+impl<R: Rng> Clone for SharedHotText<R> {
+    fn clone(&self) -> Self {
+        SharedHotText {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<R: Rng + Clone> SharedHotText<R> {
+    pub fn new(ht: HotText<R>) -> Self {
+        SharedHotText {
+            inner: Arc::new(Inner {
+                current: Mutex::new(Arc::new(ht)),
+                epoch: AtomicU64::new(0),
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// The current snapshot, for queries that don't draw, like listing keys.
+    pub fn current(&self) -> Arc<HotText<R>> {
+        Arc::clone(&lock(&self.inner.current))
+    }
+
+    /// The number of snapshots swapped in since the handle was created.
+    pub fn epoch(&self) -> u64 {
+        self.inner.epoch.load(Ordering::Acquire)
+    }
+
+    /// Applies `update` to a copy of the current snapshot, e.g. reloading a
+    /// changed file, and swaps the copy in if it succeeds. Readers keep
+    /// drawing from the old snapshot until then.
+    ///
+    /// The copy keeps the snapshot's write-through file, so edits made by
+    /// `update` are written back. Readers' copies never write through.
+    pub fn reload<T, E, F>(&self, update: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut HotText<R>) -> Result<T, E>,
+    {
+        let _writer = lock(&self.inner.writer);
+        let current = self.current();
+        let mut next = HotText::clone(&current);
+        // Only the writer holds the file, and it replaces the snapshot.
+        next.write_through = current.write_through.clone();
+        let result = update(&mut next)?;
+        self.swap(next);
+        Ok(result)
+    }
+
+    /// Replaces the content with a collection built elsewhere.
+    pub fn store(&self, ht: HotText<R>) {
+        let _writer = lock(&self.inner.writer);
+        self.swap(ht);
+    }
+
+    /// Creates a reader drawing with its own `rng`.
+    pub fn reader(&self, rng: R) -> SharedReader<R> {
+        let current = lock(&self.inner.current);
+        SharedReader {
+            epoch: self.epoch(),
+            local: current.clone_with_rng(rng),
+            shared: self.clone(),
+        }
+    }
+
+    fn swap(&self, ht: HotText<R>) {
+        let mut current = lock(&self.inner.current);
+        *current = Arc::new(ht);
+        self.inner.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// A thread's view of a [`SharedHotText`], created with
+/// [`SharedHotText::reader()`].
+///
+/// Draw state such as history, cooldowns and sequence positions belongs to the
+/// reader's copy, and starts over from the snapshot when a reload is picked up.
+#[derive(Debug)]
+pub struct SharedReader<R: Rng> {
+    shared: SharedHotText<R>,
+    epoch: u64,
+    local: HotText<R>,
+}
+
+impl<R: Rng + Clone> SharedReader<R> {
+    /// The reader's copy of the content, refreshed first if a reload was
+    /// swapped in since the last call.
+    pub fn latest(&mut self) -> &mut HotText<R> {
+        if self.shared.epoch() != self.epoch {
+            let current = lock(&self.shared.inner.current);
+            // Read under the lock so the epoch matches the snapshot.
+            self.epoch = self.shared.epoch();
+            let current = Arc::clone(&current);
+            self.local = current.clone_with_rng(self.local.rng.clone());
+        }
+        &mut self.local
+    }
+}
+
+/// Content shared between copies of a collection, and copied only when one of
+/// them changes it. Reader copies share their snapshot's lines this way, so
+/// picking up a reload only copies draw state.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CowArc<T>(Arc<T>);
+
+impl<T> Clone for CowArc<T> {
+    fn clone(&self) -> Self {
+        CowArc(Arc::clone(&self.0))
+    }
+}
+
+impl<T> CowArc<T> {
+    /// Whether both share the same content.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T> From<T> for CowArc<T> {
+    fn from(value: T) -> Self {
+        CowArc(Arc::new(value))
+    }
+}
+
+impl<T> Deref for CowArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone> DerefMut for CowArc<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+/// Locks `mutex`, ignoring poisoning: it only guards a pointer swap, which
+/// can't be left half done.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn reload_while_reading() {
+        let mut ht = HotText::new(StdRng::seed_from_u64(0));
+        ht.insert("greet", "Hi!").unwrap();
+        let shared = SharedHotText::new(ht);
+
+        let readers: Vec<_> = (0..4)
+            .map(|i| {
+                let mut reader = shared.reader(StdRng::seed_from_u64(i));
+                thread::spawn(move || {
+                    let start = Instant::now();
+                    while start.elapsed() < Duration::from_secs(5) {
+                        match reader.latest().get_line_raw("greet").unwrap().as_str() {
+                            "Hi!" => thread::yield_now(),
+                            "Hello!" => return true,
+                            line => panic!("unexpected line {:?}", line),
+                        }
+                    }
+                    false
+                })
+            })
+            .collect();
+        shared
+            .reload(|ht| {
+                ht.remove("greet", "Hi!")?;
+                ht.insert("greet", "Hello!")
+            })
+            .unwrap();
+
+        for reader in readers {
+            assert!(reader.join().unwrap());
+        }
+        assert_eq!(shared.epoch(), 1);
+    }
+
+    #[test]
+    fn share_content_with_readers() {
+        let mut ht = HotText::new(StdRng::seed_from_u64(0));
+        ht.insert("greet", "Hi!").unwrap();
+        let shared = SharedHotText::new(ht);
+        let mut reader = shared.reader(StdRng::seed_from_u64(1));
+        shared.reload(|ht| ht.insert("bye", "Bye!")).unwrap();
+
+        assert_eq!(reader.latest().get_line_raw("bye").unwrap(), "Bye!");
+        reader.latest().get_line_raw("greet").unwrap();
+        let current = shared.current();
+        assert!(Arc::ptr_eq(
+            &reader.local.line_pairs.0,
+            &current.line_pairs.0
+        ));
+        assert!(Arc::ptr_eq(&reader.local.line_meta.0, &current.line_meta.0));
+    }
+
+    #[test]
+    fn reload_with_write_through() {
+        let file = std::env::temp_dir().join("hottext_shared_write_through.json");
+        let _ = std::fs::remove_file(&file);
+        let ht = HotText::new(StdRng::seed_from_u64(0))
+            .with_write_through(&file)
+            .unwrap();
+        let shared = SharedHotText::new(ht);
+        shared.reload(|ht| ht.insert("greet", "Hi!")).unwrap();
+        shared.reload(|ht| ht.insert("greet", "Hello!")).unwrap();
+
+        let loaded = HotText::new(StdRng::seed_from_u64(1))
+            .with_load(&file)
+            .unwrap();
+        assert_eq!(loaded.get("greet").unwrap().len(), 2);
+        let mut reader = shared.reader(StdRng::seed_from_u64(2));
+        assert!(reader.latest().write_through.is_none());
+    }
+
+    #[test]
+    fn keep_snapshot_on_failed_reload() {
+        let shared = SharedHotText::new(HotText::new(StdRng::seed_from_u64(0)));
+        shared.reload(|ht| ht.insert("greet", "Hi!")).unwrap();
+        let mut reader = shared.reader(StdRng::seed_from_u64(1));
+
+        assert!(shared
+            .reload(|ht| {
+                ht.insert("greet", "Hello!")?;
+                ht.load("./does_not_exist.json")
+            })
+            .is_err());
+        assert_eq!(shared.epoch(), 1);
+        assert_eq!(shared.current().get("greet").unwrap().len(), 1);
+        assert_eq!(reader.latest().get_line_raw("greet").unwrap(), "Hi!");
+
+        shared.store(HotText::new(StdRng::seed_from_u64(2)));
+        assert!(reader.latest().get_line_raw("greet").is_none());
+    }
+}
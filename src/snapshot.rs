@@ -0,0 +1,108 @@
+//! Snapshots of loaded content for rolling back failed edits or reloads.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::layer::Layer;
+use crate::shared::CowArc;
+use crate::{HotText, LineMetas, LinePairs};
+
+/// A copy of a collection's content, taken with [`HotText::snapshot()`].
+///
+/// Only content is captured: lines, their metadata, sequences and layers. Settings such
+/// as the profile or normalization are left as they are on restore.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    line_pairs: CowArc<LinePairs>,
+    line_meta: CowArc<LineMetas>,
+    sequences: HashMap<String, Vec<String>>,
+    layers: CowArc<Vec<Layer>>,
+}
+
+impl<R: Rng> HotText<R> {
+    /// Takes a snapshot of the current content.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            line_pairs: self.line_pairs.clone(),
+            line_meta: self.line_meta.clone(),
+            sequences: self.sequences.clone(),
+            layers: self.layers.clone(),
+        }
+    }
+
+    /// Replaces the current content with a snapshot, undoing everything loaded,
+    /// inserted or patched since it was taken.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.observe_changes(|ht| {
+            ht.line_pairs = snapshot.line_pairs;
+            ht.line_meta = snapshot.line_meta;
+            ht.sequences = snapshot.sequences;
+            ht.layers = snapshot.layers;
+        })
+    }
+
+    /// Applies a batch of edits all at once, or not at all, e.g.
+    /// `ht.transaction(|tx| { tx.insert("greet", "Hi!")?; tx.load("extra.toml") })`.
+    ///
+    /// If `edits` fails, the content is restored to how it was before. Observers
+    /// are notified once for the whole batch, and a write-through file is written
+    /// once it succeeds.
+    pub fn transaction<T>(
+        &mut self,
+        edits: impl FnOnce(&mut Self) -> Result<T, Box<dyn Error>>,
+    ) -> Result<T, Box<dyn Error>> {
+        let snapshot = self.snapshot();
+        self.batch(|ht| {
+            let result = edits(ht);
+            if result.is_err() {
+                ht.restore(snapshot);
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_lines;
+
+    #[test]
+    fn restore_after_failed_load() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_load("./test_lines.toml")
+            .unwrap();
+        let snapshot = ht.snapshot();
+
+        ht.insert("meta.welcome", "Half-updated!").unwrap();
+        assert!(ht.load("./test_includes/cycle_a.json").is_err());
+        ht.restore(snapshot);
+
+        assert!(!get_lines!(ht, "meta.welcome").contains("Half-updated!"));
+        assert_eq!(get_lines!(ht, "combat.encounter").len(), 4);
+    }
+
+    #[test]
+    fn transactions() {
+        let mut ht = HotText::new(rand::thread_rng());
+        let changes = ht.subscribe_changes();
+
+        let result = ht.transaction(|tx| {
+            tx.insert("greet", "Hi!")?;
+            tx.load("./test_includes/cycle_a.json")
+        });
+        assert!(result.is_err());
+        assert!(ht.get("greet").is_none());
+        assert!(changes.try_recv().is_err());
+
+        ht.transaction(|tx| {
+            tx.insert("greet", "Hi!")?;
+            tx.insert("bye", "Bye!")
+        })
+        .unwrap();
+        assert_eq!(changes.try_recv().unwrap().added, vec!["bye", "greet"]);
+        assert!(changes.try_recv().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! The retrieval surface as a trait, so application code can swap in fakes.
+
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::HotText;
+#[cfg(feature = "templates")]
+use crate::{Capitalization, Compiled, TemplateCompileError};
+
+/// Retrieves and renders lines by key.
+///
+/// Implemented by [`HotText`] and [`ScriptedSource`], so code depending on
+/// `&mut dyn TextSource` can be tested with predetermined lines.
+pub trait TextSource {
+    /// See [`HotText::get_line_raw()`].
+    fn get_line_raw(&mut self, key: &str) -> Option<String>;
+
+    /// See [`HotText::get_lines_raw()`].
+    fn get_lines_raw(&mut self, key: &str) -> Option<HashSet<String>>;
+
+    /// See [`HotText::render_line()`].
+    #[cfg(feature = "templates")]
+    fn render_line(&mut self, key: &str, data: &[(&str, &str)]) -> Result<String, Box<dyn Error>>;
+}
+
+impl<R: Rng> TextSource for HotText<R> {
+    fn get_line_raw(&mut self, key: &str) -> Option<String> {
+        HotText::get_line_raw(self, key)
+    }
+
+    fn get_lines_raw(&mut self, key: &str) -> Option<HashSet<String>> {
+        HotText::get_lines_raw(self, key)
+    }
+
+    #[cfg(feature = "templates")]
+    fn render_line(&mut self, key: &str, data: &[(&str, &str)]) -> Result<String, Box<dyn Error>> {
+        HotText::render_line(self, key, data.iter().copied())
+    }
+}
+
+/// A [`TextSource`] for tests that returns scripted lines for each key in
+/// order, starting over after the last one.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedSource {
+    lines: HashMap<String, (Vec<String>, usize)>,
+}
+
+impl ScriptedSource {
+    pub fn new() -> Self {
+        ScriptedSource::default()
+    }
+
+    /// Adds a line to the script of `key`.
+    pub fn push(&mut self, key: &str, line: &str) {
+        self.lines
+            .entry(key.to_string())
+            .or_default()
+            .0
+            .push(line.to_string());
+    }
+
+    /// Chainable variant of [`ScriptedSource::push()`]
+    pub fn with_line(mut self, key: &str, line: &str) -> Self {
+        self.push(key, line);
+        self
+    }
+}
+
+impl TextSource for ScriptedSource {
+    fn get_line_raw(&mut self, key: &str) -> Option<String> {
+        let (lines, next) = self.lines.get_mut(key)?;
+        let line = lines.get(*next % lines.len())?.clone();
+        *next += 1;
+        Some(line)
+    }
+
+    fn get_lines_raw(&mut self, key: &str) -> Option<HashSet<String>> {
+        let (lines, _) = self.lines.get(key)?;
+        Some(lines.iter().cloned().collect())
+    }
+
+    #[cfg(feature = "templates")]
+    fn render_line(&mut self, key: &str, data: &[(&str, &str)]) -> Result<String, Box<dyn Error>> {
+        let line = self.get_line_raw(key).ok_or(TemplateCompileError {})?;
+        let data: HashMap<&str, &str> = data.iter().copied().collect();
+        Compiled::new(&line, Capitalization::Preserve, &Default::default())?.render_to_string(&data)
+    }
+}
+
+#[cfg(all(test, feature = "templates"))]
+mod tests {
+    use super::*;
+
+    fn greet(source: &mut dyn TextSource, name: &str) -> String {
+        source
+            .render_line("greet", &[("name", name)])
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn swap_sources() {
+        let mut fake = ScriptedSource::new()
+            .with_line("greet", "Hi, {{name}}!")
+            .with_line("greet", "Bye, {{name}}.");
+        assert_eq!(greet(&mut fake, "Jake"), "Hi, Jake!");
+        assert_eq!(greet(&mut fake, "Jake"), "Bye, Jake.");
+        assert_eq!(greet(&mut fake, "Jake"), "Hi, Jake!");
+        assert_eq!(fake.get_lines_raw("greet").unwrap().len(), 2);
+        assert!(fake.get_line_raw("missing").is_none());
+
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("greet", "Hello, {{name}}!").unwrap();
+        assert_eq!(greet(&mut ht, "Jake"), "Hello, Jake!");
+    }
+}
@@ -0,0 +1,182 @@
+//! Optional log of missing keys and failed renders, for shipping to analytics.
+
+use std::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "templates")]
+use std::error::Error;
+use std::time::SystemTime;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::HotText;
+#[cfg(feature = "templates")]
+use crate::{MissingKeyError, TemplateCompileError};
+
+/// Why a line couldn't be returned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissKind {
+    /// The key had no lines, even after following fallbacks.
+    MissingKey,
+    /// A line was drawn but failed to render or was rejected by a filter.
+    RenderFailed { error: String },
+}
+
+/// A missing key or failed render.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Miss {
+    pub key: String,
+    pub kind: MissKind,
+    pub timestamp: SystemTime,
+    /// The data the line was requested with, if any.
+    pub data: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MissLog {
+    capacity: usize,
+    misses: VecDeque<Miss>,
+}
+
+impl<R: Rng> HotText<R> {
+    /// Starts logging up to `capacity` misses, dropping the oldest beyond that,
+    /// or stops logging with `None`.
+    ///
+    /// Changing the capacity clears the logged misses.
+    pub fn set_miss_log(&mut self, capacity: Option<usize>) {
+        self.miss_log = capacity.map(|capacity| MissLog {
+            capacity,
+            misses: VecDeque::new(),
+        });
+    }
+
+    /// Chainable variant of [`HotText::set_miss_log()`]
+    pub fn with_miss_log(mut self, capacity: usize) -> Self {
+        self.set_miss_log(Some(capacity));
+        self
+    }
+
+    /// Gets the logged misses, oldest first.
+    pub fn misses(&self) -> Vec<&Miss> {
+        self.miss_log
+            .iter()
+            .flat_map(|log| log.misses.iter())
+            .collect()
+    }
+
+    /// Takes the logged misses, oldest first, leaving the log empty.
+    pub fn drain_misses(&mut self) -> Vec<Miss> {
+        self.miss_log
+            .iter_mut()
+            .flat_map(|log| log.misses.drain(..))
+            .collect()
+    }
+
+    /// Takes the logged misses as a JSON array.
+    pub fn export_misses_json(&mut self) -> String {
+        serde_json::to_string(&self.drain_misses()).expect("misses serialize to JSON")
+    }
+
+    pub(crate) fn record_miss(&mut self, key: &str, kind: MissKind, data: &[(&str, &str)]) {
+        let log = match &mut self.miss_log {
+            Some(log) if log.capacity > 0 => log,
+            _ => return,
+        };
+        if log.misses.len() == log.capacity {
+            log.misses.pop_front();
+        }
+        log.misses.push_back(Miss {
+            key: key.to_string(),
+            kind,
+            timestamp: SystemTime::now(),
+            data: data
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        });
+    }
+
+    /// Logs a failed render, if `result` is one.
+    #[cfg(feature = "templates")]
+    pub(crate) fn track_miss<T>(
+        &mut self,
+        key: &str,
+        data: &[(&str, &str)],
+        result: Result<T, Box<dyn Error>>,
+    ) -> Result<T, Box<dyn Error>> {
+        if let Err(error) = &result {
+            let kind = if error.is::<TemplateCompileError>() || error.is::<MissingKeyError>() {
+                MissKind::MissingKey
+            } else {
+                MissKind::RenderFailed {
+                    error: error.to_string(),
+                }
+            };
+            self.record_miss(key, kind, data);
+        }
+        result
+    }
+}
+
+#[cfg(all(test, feature = "templates"))]
+mod tests {
+    use super::*;
+    use crate::WordFilter;
+
+    #[test]
+    fn log_misses() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_miss_log(2)
+            .with_content_filter(WordFilter::reject(vec!["darn"]));
+        ht.insert("curse", "{{name}}, you darn fool!").unwrap();
+
+        assert!(ht.get_line_raw("missing").is_none());
+        assert!(ht
+            .render_line("missing.too", vec![("name", "Jake")])
+            .is_err());
+        assert!(ht.render_line("curse", vec![("name", "Jake")]).is_err());
+
+        let misses = ht.misses();
+        assert_eq!(misses.len(), 2);
+        assert_eq!(misses[0].key, "missing.too");
+        assert_eq!(misses[0].kind, MissKind::MissingKey);
+        assert_eq!(misses[0].data["name"], "Jake");
+        assert!(matches!(misses[1].kind, MissKind::RenderFailed { .. }));
+
+        let json = ht.export_misses_json();
+        assert!(json.contains("\"key\":\"curse\""));
+        assert!(json.contains("render_failed"));
+        assert!(ht.misses().is_empty());
+
+        ht.set_miss_log(None);
+        ht.get_line_raw("missing");
+        assert!(ht.drain_misses().is_empty());
+    }
+
+    #[test]
+    fn log_misses_from_every_render() {
+        let mut ht = HotText::new(rand::thread_rng())
+            .with_miss_log(10)
+            .with_content_filter(WordFilter::reject(vec!["darn"]));
+        ht.insert("curse", "{{name}}, you darn fool!").unwrap();
+        let data = [("name", "Jake")];
+
+        assert!(ht.render_all("curse", data.iter().copied()).is_err());
+        assert!(ht.render_all("missing", data.iter().copied()).is_err());
+        let results = ht.render_many(&[("curse", &data[..]), ("missing", &data[..])]);
+        assert!(results.iter().all(Result::is_err));
+        assert!(ht
+            .render_line_cached("missing", "visit", data.iter().copied())
+            .is_err());
+        assert!(ht
+            .render_line_to("curse", data.iter().copied(), &mut Vec::new())
+            .is_err());
+
+        let kinds: Vec<bool> = ht
+            .drain_misses()
+            .iter()
+            .map(|miss| miss.kind == MissKind::MissingKey)
+            .collect();
+        assert_eq!(kinds, vec![false, true, false, true, true, false]);
+    }
+}
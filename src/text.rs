@@ -0,0 +1,163 @@
+//! A minimal line-oriented content format.
+//!
+//! ```text
+//! # Comments start with a hash.
+//! [combat.encounter]
+//! You encounter a lion!
+//! You stumble across a tiger!
+//! ```
+//!
+//! Every non-blank line below a `[key]` header is one variant of that key,
+//! trimmed. A line that should literally start with `#`, `[` or `\`, or keep
+//! its surrounding whitespace, is escaped with a leading backslash: everything
+//! after it is taken as is.
+
+use std::fmt;
+
+use crate::schema::Duplicates;
+use crate::LinePairs;
+
+#[derive(Debug)]
+pub struct TextParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TextParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TextParseError on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TextParseError {}
+
+/// Parses plain-text content into key/line pairs, recording lines repeated
+/// within a key in `duplicates`.
+pub(crate) fn parse(
+    content: &str,
+    duplicates: &mut Duplicates,
+) -> Result<LinePairs, TextParseError> {
+    let mut line_pairs = LinePairs::default();
+    let mut current: Option<String> = None;
+
+    for (i, raw) in content.lines().enumerate() {
+        let trimmed = raw.trim();
+        let escaped = raw.trim_start().strip_prefix('\\');
+        if escaped.is_none() && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            continue;
+        }
+        if escaped.is_none() && trimmed.starts_with('[') {
+            let key = trimmed
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .ok_or_else(|| TextParseError {
+                    line: i + 1,
+                    message: format!("malformed key header `{}`", trimmed),
+                })?;
+            line_pairs.entry(key.to_string()).or_default();
+            current = Some(key.to_string());
+            continue;
+        }
+        let key = current.as_ref().ok_or_else(|| TextParseError {
+            line: i + 1,
+            message: "line appears before any [key] header".to_string(),
+        })?;
+        let line = escaped.unwrap_or(trimmed);
+        if !line_pairs
+            .entry(key.clone())
+            .or_default()
+            .insert(line.to_string())
+        {
+            duplicates.push((key.clone(), line.to_string()));
+        }
+    }
+
+    Ok(line_pairs)
+}
+
+/// Serializes key/line pairs into plain-text content, sorted for stable diffs.
+pub(crate) fn to_string(line_pairs: &LinePairs) -> String {
+    let mut keys: Vec<&String> = line_pairs.keys().collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for (i, key) in keys.into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("[{}]\n", key));
+        let mut lines: Vec<&String> = line_pairs[key].iter().collect();
+        lines.sort();
+        for line in lines {
+            if line.starts_with(['#', '[', '\\']) || line.trim() != line || line.is_empty() {
+                out.push('\\');
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_text() {
+        let line_pairs = parse(
+            "# Greetings\n[meta.welcome]\nHello!\n\n[combat.encounter]\nA lion!\n\\# not a comment\n",
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert!(line_pairs["meta.welcome"].contains("Hello!"));
+        assert_eq!(line_pairs["combat.encounter"].len(), 2);
+        assert!(line_pairs["combat.encounter"].contains("# not a comment"));
+    }
+
+    #[test]
+    fn parse_text_errors() {
+        assert_eq!(parse("orphan line", &mut Vec::new()).unwrap_err().line, 1);
+        assert_eq!(
+            parse("[ok]\nfine\n[broken\n", &mut Vec::new())
+                .unwrap_err()
+                .line,
+            3
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let line_pairs =
+            parse("[a]\n\\[not a header\nplain\n\n[b]\none\n", &mut Vec::new()).unwrap();
+        assert_eq!(
+            parse(&to_string(&line_pairs), &mut Vec::new()).unwrap(),
+            line_pairs
+        );
+    }
+
+    #[test]
+    fn round_trip_whitespace_and_comments() {
+        let mut line_pairs = LinePairs::default();
+        line_pairs.insert(
+            "a".to_string(),
+            vec![
+                "  indented",
+                "trailing ",
+                "# not a comment",
+                " # nor this",
+                "\\",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+        assert_eq!(
+            parse(&to_string(&line_pairs), &mut Vec::new()).unwrap(),
+            line_pairs
+        );
+    }
+}
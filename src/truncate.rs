@@ -0,0 +1,146 @@
+//! Cutting text to a number of visible characters without splitting any.
+
+#[cfg(feature = "templates")]
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// Appended to truncated text.
+pub const ELLIPSIS: &str = "…";
+
+/// Whether `c` extends the character before it rather than starting a new
+/// visible one: combining marks, variation selectors, emoji modifiers and tags.
+fn is_extending(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0900..=0x0903
+            | 0x093A..=0x094F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D
+            | 0x20D0..=0x20FF
+            | 0x3099..=0x309A
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// The byte offsets where each visible character (grapheme cluster) of
+/// `text` starts. Covers combining marks, emoji ZWJ sequences, skin tones
+/// and flags, though not every rule of Unicode segmentation.
+pub(crate) fn grapheme_starts(text: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut regional_run = 0;
+    for (i, c) in text.char_indices() {
+        let joined = previous == Some('\u{200D}');
+        let flag_pair = is_regional_indicator(c) && regional_run % 2 == 1;
+        let crlf = previous == Some('\r') && c == '\n';
+        if previous.is_none() || !(is_extending(c) || joined || flag_pair || crlf) {
+            starts.push(i);
+        }
+        regional_run = if is_regional_indicator(c) {
+            regional_run + 1
+        } else {
+            0
+        };
+        previous = Some(c);
+    }
+    starts
+}
+
+/// Cuts `text` to at most `width` visible characters, ending with [`ELLIPSIS`]
+/// if anything was cut. Characters made of several code points, like accented
+/// letters and emoji, are never split.
+pub fn truncate(text: &str, width: usize) -> String {
+    let starts = grapheme_starts(text);
+    if starts.len() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let kept = text[..starts[width - 1]].trim_end();
+    format!("{}{}", kept, ELLIPSIS)
+}
+
+/// `{{truncate value width}}`: cuts a value with [`truncate()`].
+#[cfg(feature = "templates")]
+pub(crate) fn truncate_value(args: &[String]) -> Result<String, String> {
+    match args {
+        [value, width] => {
+            let width = width
+                .parse()
+                .map_err(|_| format!("`{}` is not a width", width))?;
+            Ok(truncate(value, width))
+        }
+        _ => Err("expected a value and a width".to_string()),
+    }
+}
+
+impl<R: Rng> HotText<R> {
+    /// Variant of [`HotText::render_line()`] that cuts the rendered line to
+    /// at most `width` visible characters with [`truncate()`], for UI slots
+    /// with hard width limits.
+    #[cfg(feature = "templates")]
+    pub fn render_line_truncated<'a, D: IntoIterator<Item = (&'a str, &'a str)>>(
+        &mut self,
+        key: &str,
+        data: D,
+        width: usize,
+    ) -> Result<String, Box<dyn Error>> {
+        Ok(truncate(&self.render_line(key, data)?, width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_graphemes() {
+        assert_eq!(truncate("Hello, world", 20), "Hello, world");
+        assert_eq!(truncate("Hello, world", 8), "Hello,…");
+        assert_eq!(truncate("Hello", 0), "");
+        // Decomposed accents, a family emoji, a flag and a skin tone stay whole.
+        assert_eq!(truncate("cafe\u{301}s", 5), "cafe\u{301}s");
+        assert_eq!(truncate("cafe\u{301}s", 4), "caf…");
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(
+            truncate(&format!("{0}{0}{0}", family), 2),
+            format!("{}…", family)
+        );
+        assert_eq!(
+            grapheme_starts("\u{1F1EF}\u{1F1F5}\u{1F44B}\u{1F3FD}!").len(),
+            3
+        );
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn render_truncated() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert("title", "The {{adjective}} Sword of Destiny")
+            .unwrap();
+        assert_eq!(
+            ht.render_line_truncated("title", vec![("adjective", "Legendary")], 16)
+                .unwrap(),
+            "The Legendary S…"
+        );
+        assert!(truncate_value(&["text".to_string(), "wide".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Finding where keys are declared in content files and used in Rust source,
+//! for editor tooling.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use crate::format::{self, Format};
+#[cfg(feature = "templates")]
+use crate::partial;
+use crate::{include, HotText};
+
+/// Methods whose first argument is a key.
+const KEYED_METHODS: &[&str] = &[
+    "get_distinct_lines",
+    "get_line",
+    "get_line_cached",
+    "get_line_for",
+    "get_line_for_user",
+    "get_line_or",
+    "get_line_raw",
+    "get_line_with_id",
+    "get_lines_raw",
+    "next_line",
+    "next_line_with",
+    "render_line",
+    "render_line_cached",
+    "render_line_data",
+    "render_line_escaped",
+    "render_line_for_user",
+    "render_line_into",
+    "render_line_or",
+    "render_line_spans",
+    "render_line_to",
+    "render_line_truncated",
+    "render_line_unprocessed",
+    "render_line_with",
+];
+
+/// Where a key appears in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLocation {
+    pub key: String,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column of the key's first character, counted in characters.
+    pub column: usize,
+}
+
+/// A key used in a Rust source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyUsage {
+    pub file: PathBuf,
+    pub location: KeyLocation,
+}
+
+impl fmt::Display for KeyUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: `{}`",
+            self.file.display(),
+            self.location.line,
+            self.location.column,
+            self.location.key
+        )
+    }
+}
+
+/// Finds the keys declared in a content file, using the format implied by
+/// `path`'s extension. Files in other formats have no declarations.
+pub fn scan_declarations(path: &Path, content: &str) -> Vec<KeyLocation> {
+    let format = match Format::from_path(path) {
+        Some(format) => format,
+        None => return Vec::new(),
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let key = format::declared_key(line.trim_end(), format)?;
+            let column = line
+                .find(&key)
+                .map_or(0, |start| line[..start].chars().count());
+            (!key.is_empty() && key != crate::SCHEMA_VERSION_KEY).then(|| KeyLocation {
+                key,
+                line: i + 1,
+                column: column + 1,
+            })
+        })
+        .collect()
+}
+
+/// Finds the keys passed as string literals to `get_line`, `render_line` and
+/// their variants in Rust source, e.g. `ht.render_line("combat.slay", data)`.
+/// Keys built at runtime can't be found.
+pub fn scan_usages(source: &str) -> Vec<KeyLocation> {
+    let mut usages = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let mut rest = line;
+        while let Some(open) = rest.find('(') {
+            let before = &rest[..open];
+            let name_start = before
+                .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map_or(0, |i| i + 1);
+            let after = rest[open + 1..].trim_start();
+            if KEYED_METHODS.contains(&&before[name_start..]) && after.starts_with('"') {
+                if let Some(end) = after[1..].find('"') {
+                    let start = line.len() - after.len() + 1;
+                    usages.push(KeyLocation {
+                        key: after[1..end + 1].to_string(),
+                        line: i + 1,
+                        column: line[..start].chars().count() + 1,
+                    });
+                }
+            }
+            rest = &rest[open + 1..];
+        }
+    }
+    usages
+}
+
+/// Finds the keys used by every Rust source file under `dir`, like
+/// [`scan_usages()`], sorted by file. Hidden directories and `target` are
+/// skipped.
+pub fn scan_sources<P: AsRef<Path>>(dir: P) -> io::Result<Vec<KeyUsage>> {
+    let mut files = Vec::new();
+    source_files(dir.as_ref(), &mut files)?;
+    files.sort();
+    let mut usages = Vec::new();
+    for file in files {
+        let source = fs::read_to_string(&file)?;
+        usages.extend(scan_usages(&source).into_iter().map(|location| KeyUsage {
+            file: file.clone(),
+            location,
+        }));
+    }
+    Ok(usages)
+}
+
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                source_files(&path, files)?;
+            }
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets the `usages` of keys that have no lines, even through their
+    /// fallbacks, e.g. to fail a CI job when code asks for content that
+    /// writers haven't added yet:
+    ///
+    /// ```no_run
+    /// # let mut ht = hottext::HotText::new(rand::thread_rng());
+    /// ht.load("lines.toml")?;
+    /// let usages = hottext::scan_sources("src")?;
+    /// for usage in ht.missing_keys(&usages) {
+    ///     eprintln!("{}: no lines", usage);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn missing_keys<'a>(&self, usages: &'a [KeyUsage]) -> Vec<&'a KeyUsage> {
+        usages
+            .iter()
+            .filter(|usage| !self.can_draw(&usage.location.key))
+            .collect()
+    }
+
+    /// Gets the keys with lines that no code can draw, sorted, so stale
+    /// content can be pruned: keys that aren't in `usages`, or reached from
+    /// them through fallbacks and partials, or part of a composite or
+    /// experiment.
+    ///
+    /// Keys built at runtime, like `format!("enemy.{}.taunt", kind)`, can't be
+    /// scanned for, so keys matching any `allowlist` pattern are never
+    /// reported. Patterns use `*` and `?` wildcards, e.g. `enemy.*.taunt`.
+    pub fn unused_keys(&self, usages: &[KeyUsage], allowlist: &[&str]) -> Vec<&str> {
+        let mut pending: Vec<&str> =
+            usages
+                .iter()
+                .map(|usage| usage.location.key.as_str())
+                .chain(
+                    self.composites
+                        .values()
+                        .flat_map(|composite| composite.keys.iter().map(String::as_str)),
+                )
+                .chain(self.experiments.values().flat_map(|experiment| {
+                    experiment.buckets.iter().map(|(_, _, key)| key.as_str())
+                }))
+                .collect();
+        let mut used = HashSet::new();
+        while let Some(key) = pending.pop() {
+            if !used.insert(key) {
+                continue;
+            }
+            if let Some(fallback) = self.fallbacks.get(key) {
+                pending.push(fallback);
+            }
+            #[cfg(feature = "templates")]
+            for lines in self.lines_of(key) {
+                pending.extend(lines.iter().flat_map(|line| partial::partial_keys(line)));
+            }
+        }
+        self.drawable_keys()
+            .into_iter()
+            .map(String::as_str)
+            .filter(|key| !used.contains(key))
+            .filter(|key| {
+                !allowlist
+                    .iter()
+                    .any(|pattern| include::wildcard_match(pattern, key))
+            })
+            .collect()
+    }
+
+    /// The line sets of `key` in the base content and enabled layers.
+    #[cfg(feature = "templates")]
+    fn lines_of<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a HashSet<String>> {
+        self.line_pairs.get(key).into_iter().chain(
+            self.layers
+                .iter()
+                .filter(|layer| layer.enabled)
+                .filter_map(move |layer| layer.line_pairs.get(key)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_keys() {
+        let source = "let line = ht.get_line(\"meta.welcome\")?;\n\
+                      ht.render_line( \"combat.slay\", vec![(\"weapon\", \"axe\")])\n\
+                      ht.get_line_by_tag(\"sarcastic\"); ht.get_line(&key);\n";
+        assert_eq!(
+            scan_usages(source),
+            vec![
+                KeyLocation {
+                    key: "meta.welcome".to_string(),
+                    line: 1,
+                    column: 25,
+                },
+                KeyLocation {
+                    key: "combat.slay".to_string(),
+                    line: 2,
+                    column: 18,
+                },
+            ]
+        );
+
+        let content = std::fs::read_to_string("./test_lines.toml").unwrap();
+        let declarations = scan_declarations(Path::new("test_lines.toml"), &content);
+        assert_eq!(declarations[0].key, "meta.welcome");
+        assert_eq!((declarations[0].line, declarations[0].column), (1, 2));
+        assert!(scan_declarations(Path::new("notes.rs"), &content).is_empty());
+    }
+
+    #[test]
+    fn find_missing_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.load("./test_lines.toml").unwrap();
+        let usages = scan_sources("./test_sources").unwrap();
+        assert_eq!(usages.len(), 4);
+
+        let missing = ht.missing_keys(&usages);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].location.key, "combat.flee");
+        assert_eq!(missing[0].location.line, 8);
+        assert!(missing[0].to_string().ends_with(":8:36: `combat.flee`"));
+
+        ht.set_fallback("combat.flee", "combat.encounter");
+        assert!(ht.missing_keys(&usages).is_empty());
+    }
+
+    #[test]
+    fn find_unused_keys() {
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.load("./test_lines.toml").unwrap();
+        ht.insert("combat.retreat", "You fall back.").unwrap();
+        ht.insert("enemy.orc.taunt", "Grr!").unwrap();
+        ht.insert("stale", "Nobody says this.").unwrap();
+        ht.set_fallback("combat.flee", "combat.retreat");
+        let usages = scan_sources("./test_sources").unwrap();
+
+        assert_eq!(ht.unused_keys(&usages, &["enemy.*.taunt"]), vec!["stale"]);
+        assert_eq!(
+            ht.unused_keys(&usages, &[]),
+            vec!["enemy.orc.taunt", "stale"]
+        );
+
+        #[cfg(feature = "templates")]
+        {
+            ht.insert("combat.retreat", "Fall back! {{> combat.taunt}}")
+                .unwrap();
+            ht.insert("combat.taunt", "Cowards!").unwrap();
+            assert_eq!(ht.unused_keys(&usages, &["enemy.*"]), vec!["stale"]);
+        }
+    }
+}
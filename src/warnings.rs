@@ -0,0 +1,320 @@
+//! Non-fatal problems noticed while loading content.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+
+use crate::{HotText, LinePairs};
+
+/// What to do with empty lines and keys without any lines while loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyPolicy {
+    /// Drop them silently.
+    Ignore,
+    /// Drop them and record a [`LoadWarning`].
+    #[default]
+    Warn,
+    /// Fail the load with an [`EmptyContentError`].
+    Error,
+}
+
+#[derive(Debug)]
+pub struct EmptyContentError {
+    pub kind: LoadWarningKind,
+}
+
+impl fmt::Display for EmptyContentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "EmptyContentError: {}",
+            LoadWarning {
+                file: None,
+                kind: self.kind.clone(),
+            }
+        )
+    }
+}
+
+impl std::error::Error for EmptyContentError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadWarningKind {
+    /// A key that is the empty string.
+    EmptyKey,
+    /// A key that was declared without any lines.
+    NoLines { key: String },
+    /// A line that is the empty string.
+    EmptyLine { key: String },
+    /// A line with leading/trailing whitespace, tabs, or repeated spaces.
+    SuspiciousWhitespace { key: String, line: String },
+    /// A line repeated within a key of one file, or already loaded under the
+    /// same key.
+    DuplicateLine { key: String, line: String },
+    /// A key that violates the [`KeyConvention`](crate::KeyConvention).
+    KeyConvention { key: String, reason: String },
+    /// A plain line longer than the [maximum length](crate::HotText::set_max_length())
+    /// of its key, in visible characters.
+    TooLong {
+        key: String,
+        line: String,
+        length: usize,
+        max: usize,
+    },
+    /// A line breaking a [charset rule](crate::CharsetRule) of its key.
+    ForbiddenContent {
+        key: String,
+        line: String,
+        reason: String,
+    },
+    /// A file written for an older [schema version](crate::SCHEMA_VERSION), or
+    /// without one.
+    OutdatedSchema { version: Option<u32>, current: u32 },
+    /// Edits that couldn't be written back to the
+    /// [write-through file](crate::HotText::set_write_through()), by an edit
+    /// that can't return an error.
+    WriteThroughFailed { error: String },
+}
+
+/// A non-fatal problem noticed while loading content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadWarning {
+    /// The file being loaded, when loading from a file.
+    pub file: Option<PathBuf>,
+    pub kind: LoadWarningKind,
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file.display())?;
+        }
+        match &self.kind {
+            LoadWarningKind::EmptyKey => write!(f, "empty key"),
+            LoadWarningKind::NoLines { key } => write!(f, "key `{}` has no lines", key),
+            LoadWarningKind::EmptyLine { key } => write!(f, "empty `{}` line", key),
+            LoadWarningKind::SuspiciousWhitespace { key, line } => {
+                write!(f, "suspicious whitespace in `{}` line {:?}", key, line)
+            }
+            LoadWarningKind::DuplicateLine { key, line } => {
+                write!(f, "duplicate `{}` line {:?}", key, line)
+            }
+            LoadWarningKind::KeyConvention { key, reason } => {
+                write!(
+                    f,
+                    "key `{}` violates the naming convention: {}",
+                    key, reason
+                )
+            }
+            LoadWarningKind::TooLong {
+                key,
+                line,
+                length,
+                max,
+            } => write!(
+                f,
+                "`{}` line {:?} is {} characters, over the limit of {}",
+                key, line, length, max
+            ),
+            LoadWarningKind::ForbiddenContent { key, line, reason } => {
+                write!(f, "`{}` line {:?} has {}", key, line, reason)
+            }
+            LoadWarningKind::OutdatedSchema { version, current } => {
+                match version {
+                    Some(version) => write!(f, "schema version {} is outdated", version)?,
+                    None => write!(f, "no schema version")?,
+                }
+                write!(f, ", add `schema_version = {}` to upgrade", current)
+            }
+            LoadWarningKind::WriteThroughFailed { error } => {
+                write!(f, "edits weren't written back: {}", error)
+            }
+        }
+    }
+}
+
+fn has_suspicious_whitespace(line: &str) -> bool {
+    line != line.trim() || line.contains("  ") || line.contains('\t')
+}
+
+impl<R: Rng> HotText<R> {
+    /// Gets the warnings collected by loaders since they were last taken.
+    pub fn warnings(&self) -> &[LoadWarning] {
+        &self.warnings
+    }
+
+    /// Takes the warnings collected by loaders, leaving none behind.
+    pub fn take_warnings(&mut self) -> Vec<LoadWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Variant of [`HotText::load()`] that returns the warnings raised by this load.
+    pub fn load_with_warnings<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+    ) -> Result<Vec<LoadWarning>, Box<dyn Error>> {
+        let first = self.warnings.len();
+        self.load(file)?;
+        Ok(self.warnings.split_off(first))
+    }
+
+    /// Sets how empty lines and keys without lines are handled while loading.
+    pub fn set_empty_policy(&mut self, policy: EmptyPolicy) {
+        self.empty_policy = policy;
+    }
+
+    /// Chainable variant of [`HotText::set_empty_policy()`]
+    pub fn with_empty_policy(mut self, policy: EmptyPolicy) -> Self {
+        self.set_empty_policy(policy);
+        self
+    }
+
+    pub(crate) fn warn(&mut self, kind: LoadWarningKind) {
+        self.warnings.push(LoadWarning { file: None, kind });
+    }
+
+    /// Applies the [`EmptyPolicy`] to one problem, returning an error if it is fatal.
+    fn handle_empty(&mut self, kind: LoadWarningKind) -> Result<(), EmptyContentError> {
+        match self.empty_policy {
+            EmptyPolicy::Ignore => Ok(()),
+            EmptyPolicy::Warn => {
+                self.warn(kind);
+                Ok(())
+            }
+            EmptyPolicy::Error => Err(EmptyContentError { kind }),
+        }
+    }
+
+    /// Removes empty lines and keys without lines according to the [`EmptyPolicy`].
+    ///
+    /// Nothing is removed if the policy turns any of them into an error.
+    pub(crate) fn apply_empty_policy(
+        &mut self,
+        line_pairs: &mut LinePairs,
+    ) -> Result<(), EmptyContentError> {
+        let mut keys: Vec<&String> = line_pairs.keys().collect();
+        keys.sort();
+        let mut problems = Vec::new();
+        for key in keys {
+            let lines = &line_pairs[key];
+            if lines.contains("") {
+                problems.push(LoadWarningKind::EmptyLine { key: key.clone() });
+            }
+            if lines.iter().all(String::is_empty) {
+                problems.push(LoadWarningKind::NoLines { key: key.clone() });
+            }
+        }
+        for problem in problems {
+            self.handle_empty(problem)?;
+        }
+
+        line_pairs.retain(|_, lines| {
+            lines.remove("");
+            !lines.is_empty()
+        });
+        Ok(())
+    }
+
+    /// Records warnings for a key and the lines about to be loaded into it.
+    pub(crate) fn check_lines(&mut self, key: &str, lines: &HashSet<String>) {
+        if key.is_empty() {
+            self.warn(LoadWarningKind::EmptyKey);
+        } else {
+            self.check_key_convention(key);
+        }
+        for line in lines {
+            self.check_line(key, line);
+        }
+    }
+
+    /// Records warnings for one line about to be loaded.
+    pub(crate) fn check_line(&mut self, key: &str, line: &str) {
+        if has_suspicious_whitespace(line) {
+            self.warn(LoadWarningKind::SuspiciousWhitespace {
+                key: key.to_string(),
+                line: line.to_string(),
+            });
+        }
+        if self
+            .line_pairs
+            .get(key)
+            .is_some_and(|lines| lines.contains(line))
+        {
+            self.warn(LoadWarningKind::DuplicateLine {
+                key: key.to_string(),
+                line: line.to_string(),
+            });
+        }
+        self.check_length(key, line);
+        self.check_charset(key, line);
+    }
+
+    /// Attributes warnings raised since `first` to `file`.
+    pub(crate) fn attribute_warnings(&mut self, first: usize, file: &Path) {
+        for warning in &mut self.warnings[first..] {
+            warning.file.get_or_insert_with(|| file.to_path_buf());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn whitespace() {
+        assert!(has_suspicious_whitespace(" leading"));
+        assert!(has_suspicious_whitespace("double  space"));
+        assert!(has_suspicious_whitespace("tab\there"));
+        assert!(!has_suspicious_whitespace("Just fine."));
+    }
+
+    #[test]
+    fn duplicates_within_file() {
+        let mut ht = HotText::new(rand::thread_rng());
+        let warnings = ht.load_with_warnings("./test_duplicates.txt").unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![LoadWarning {
+                file: Some(PathBuf::from("./test_duplicates.txt")),
+                kind: LoadWarningKind::DuplicateLine {
+                    key: "combat.taunt".to_string(),
+                    line: "Is that all you've got?".to_string(),
+                },
+            }]
+        );
+        assert_eq!(ht.get("combat.taunt").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn empty_policy() {
+        let mut line_pairs = HashMap::new();
+        line_pairs.insert(
+            "blank".to_string(),
+            vec![String::new()].into_iter().collect(),
+        );
+        line_pairs.insert(
+            "greet".to_string(),
+            vec![String::new(), "Hi!".to_string()].into_iter().collect(),
+        );
+
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.load_hashmap(line_pairs.clone()).unwrap();
+        assert_eq!(ht.take_warnings().len(), 3);
+        assert!(ht.get_line_raw("blank").is_none());
+        assert_eq!(ht.get_line_raw("greet").unwrap(), "Hi!");
+
+        let mut ht = HotText::new(rand::thread_rng()).with_empty_policy(EmptyPolicy::Ignore);
+        ht.load_hashmap(line_pairs.clone()).unwrap();
+        assert!(ht.warnings().is_empty());
+
+        let mut ht = HotText::new(rand::thread_rng()).with_empty_policy(EmptyPolicy::Error);
+        assert!(ht.load_hashmap(line_pairs).is_err());
+        assert!(ht.get_line_raw("greet").is_none());
+    }
+}
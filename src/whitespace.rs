@@ -0,0 +1,82 @@
+//! Whitespace control around template tags.
+//!
+//! A tag opened with `{{~` drops the whitespace before it, and a tag closed
+//! with `~}}` drops the whitespace after it, e.g. `Hello{{~#title}} {{title}}{{/title~}} !`.
+//! Comments like `{{! note to translators }}` render as nothing, so they
+//! combine with the markers to leave no stray spaces behind.
+
+use std::borrow::Cow;
+
+/// Applies the whitespace markers of `template` and removes them.
+pub(crate) fn trim_markers(template: &str) -> Cow<'_, str> {
+    if !template.contains("{{~") && !template.contains("~}}") {
+        return Cow::Borrowed(template);
+    }
+    let mut trimmed = String::with_capacity(template.len());
+    let mut rest = template;
+    let mut trim_next = false;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        let before = &rest[..start];
+        let before = if trim_next {
+            before.trim_start()
+        } else {
+            before
+        };
+        let tag = &rest[start + 2..end];
+        let (tag, trim_before) = match tag.strip_prefix('~') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (tag, trim_after) = match tag.strip_suffix('~') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        trimmed.push_str(if trim_before {
+            before.trim_end()
+        } else {
+            before
+        });
+        trimmed.push_str("{{");
+        trimmed.push_str(tag);
+        trimmed.push_str("}}");
+        trim_next = trim_after;
+        rest = &rest[end + 2..];
+    }
+    trimmed.push_str(if trim_next { rest.trim_start() } else { rest });
+    Cow::Owned(trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HotText;
+
+    #[test]
+    fn trim_around_tags() {
+        assert_eq!(trim_markers("a {{b}} c"), "a {{b}} c");
+        assert_eq!(
+            trim_markers("a  {{~#b}} x {{/b~}}\n c"),
+            "a{{#b}} x {{/b}}c"
+        );
+
+        let mut ht = HotText::new(rand::thread_rng());
+        ht.insert(
+            "found",
+            "You found {{item}} {{~! count is optional }} {{~#count}} (x{{count}}){{/count~}} .",
+        )
+        .unwrap();
+        assert_eq!(
+            ht.render_line("found", vec![("item", "a key")]).unwrap(),
+            "You found a key."
+        );
+        assert_eq!(
+            ht.render_line("found", vec![("item", "arrows"), ("count", "3")])
+                .unwrap(),
+            "You found arrows (x3)."
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Wrapper templates that the lines of a key are rendered into.
+
+use std::error::Error;
+
+use rand::Rng;
+
+use crate::HotText;
+
+/// Marks where a key's line goes in its wrapper template.
+pub const WRAPPED_LINE: &str = "{{$line}}";
+
+impl<R: Rng> HotText<R> {
+    /// Renders every line of `key` into `wrapper`, in place of
+    /// [`WRAPPED_LINE`], so formatting conventions like a speaker prefix and
+    /// quotes aren't repeated in each line, e.g.
+    /// `set_wrapper("npc.guard", "{{speaker}}: “{{$line}}”")`.
+    ///
+    /// Wrappers apply when rendering and may use data, helpers and partials
+    /// like the lines themselves.
+    pub fn set_wrapper(&mut self, key: &str, wrapper: &str) {
+        let key = self.normalize_key(key).into_owned();
+        self.wrappers.insert(key, wrapper.to_string());
+    }
+
+    /// Chainable variant of [`HotText::set_wrapper()`]
+    pub fn with_wrapper(mut self, key: &str, wrapper: &str) -> Self {
+        self.set_wrapper(key, wrapper);
+        self
+    }
+
+    /// Removes the wrapper of `key`, returning it if there was one.
+    pub fn remove_wrapper(&mut self, key: &str) -> Option<String> {
+        self.wrappers.remove(self.normalize_key(key).as_ref())
+    }
+
+    /// Puts a line drawn from `key` into its wrapper and expands its partials,
+    /// ready to be compiled.
+    pub(crate) fn prepare_template(
+        &mut self,
+        key: &str,
+        line: String,
+    ) -> Result<String, Box<dyn Error>> {
+        let line = match self.wrappers.get(self.normalize_key(key).as_ref()) {
+            Some(wrapper) => wrapper.replace(WRAPPED_LINE, &line),
+            None => line,
+        };
+        self.expand_partials(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_lines() {
+        let mut ht =
+            HotText::new(rand::thread_rng()).with_wrapper("npc.guard", "{{speaker}}: “{{$line}}”");
+        ht.insert("npc.guard", "Halt, {{name}}!").unwrap();
+        ht.insert("npc.guard.idle", "Quiet night.").unwrap();
+
+        assert_eq!(
+            ht.render_line("npc.guard", vec![("speaker", "Guard"), ("name", "Brom")])
+                .unwrap(),
+            "Guard: “Halt, Brom!”"
+        );
+        assert_eq!(
+            ht.render_all("npc.guard", vec![("speaker", "Guard"), ("name", "Brom")])
+                .unwrap(),
+            vec!["Guard: “Halt, Brom!”"]
+        );
+        assert_eq!(
+            ht.render_line("npc.guard.idle", vec![]).unwrap(),
+            "Quiet night."
+        );
+        ht.set_key_normalization(Some(crate::KeyNormalization::default()));
+        ht.set_wrapper("NPC_Guard", "{{$line}}!");
+        assert_eq!(
+            ht.render_line("npc.guard", vec![("name", "Brom")]).unwrap(),
+            "Halt, Brom!!"
+        );
+        assert!(ht.remove_wrapper("npc/guard").is_some());
+        assert_eq!(
+            ht.render_line("npc.guard", vec![("name", "Brom")]).unwrap(),
+            "Halt, Brom!"
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! A YAML subset for content files.
+//!
+//! Supported is what content files need: a top-level mapping of keys to either
+//! a single scalar or a sequence of scalars (block or flow style), plain,
+//! quoted and block (`|`, `>`) scalars, comments, and anchors/aliases on
+//! scalars and sequences.
+//!
+//! ```yaml
+//! combat.encounter: &encounters
+//!   - You encounter a lion!
+//!   - "Oh no! It's a bear!"
+//!   - |-
+//!     A dragon lands before you.
+//!     It looks hungry.
+//! combat.ambush: *encounters
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::schema::Duplicates;
+use crate::LinePairs;
+
+#[derive(Debug)]
+pub struct YamlParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for YamlParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "YamlParseError on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for YamlParseError {}
+
+struct Parser<'a> {
+    lines: Vec<&'a str>,
+    pos: usize,
+    anchors: HashMap<String, Vec<String>>,
+}
+
+/// Parses YAML content into key/line pairs, recording lines repeated within
+/// a key in `duplicates`.
+pub(crate) fn parse(
+    content: &str,
+    duplicates: &mut Duplicates,
+) -> Result<LinePairs, YamlParseError> {
+    let mut parser = Parser {
+        lines: content.lines().collect(),
+        pos: 0,
+        anchors: HashMap::new(),
+    };
+    let mut line_pairs = LinePairs::default();
+
+    while let Some(line) = parser.next_content_line() {
+        parser.pos += 1;
+        if indent(line) > 0 {
+            return Err(parser.error("unexpected indentation"));
+        }
+        if line.starts_with("---") || line.starts_with("...") || line.starts_with('%') {
+            continue;
+        }
+        let (key, rest) = parser.split_key(line)?;
+        let lines = parser.value(rest, 0, 0)?;
+        let set = line_pairs.entry(key.clone()).or_default();
+        for line in lines {
+            if !set.insert(line.clone()) {
+                duplicates.push((key.clone(), line));
+            }
+        }
+    }
+
+    Ok(line_pairs)
+}
+
+/// Serializes key/line pairs into YAML, sorted for stable diffs.
+pub(crate) fn to_string(line_pairs: &LinePairs) -> String {
+    let mut keys: Vec<&String> = line_pairs.keys().collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for key in keys {
+        out.push_str(&format!("{}:\n", quote(key)));
+        let mut lines: Vec<&String> = line_pairs[key].iter().collect();
+        lines.sort();
+        for line in lines {
+            if is_block_safe(line) {
+                out.push_str("  - |-\n");
+                for l in line.lines() {
+                    out.push_str("    ");
+                    out.push_str(l);
+                    out.push('\n');
+                }
+            } else {
+                out.push_str(&format!("  - {}\n", quote(line)));
+            }
+        }
+    }
+    out
+}
+
+impl<'a> Parser<'a> {
+    /// Errors refer to the line most recently consumed.
+    fn error(&self, message: &str) -> YamlParseError {
+        YamlParseError {
+            line: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    /// Skips blank and comment-only lines, returning the next line with content.
+    fn next_content_line(&mut self) -> Option<&'a str> {
+        while let Some(line) = self.lines.get(self.pos) {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                return Some(line);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    /// Splits `key: rest` into the key and whatever follows the colon.
+    fn split_key(&self, line: &'a str) -> Result<(String, &'a str), YamlParseError> {
+        let line = line.trim();
+        if line.starts_with(['"', '\'']) {
+            let (key, rest) = self.quoted(line)?;
+            let rest = rest.trim_start();
+            let rest = rest
+                .strip_prefix(':')
+                .ok_or_else(|| self.error("expected `:` after key"))?;
+            return Ok((key, rest));
+        }
+        let colon = line
+            .match_indices(':')
+            .map(|(i, _)| i)
+            .find(|&i| line[i + 1..].is_empty() || line[i + 1..].starts_with([' ', '\t']))
+            .ok_or_else(|| self.error("expected `key: value`"))?;
+        Ok((line[..colon].trim().to_string(), &line[colon + 1..]))
+    }
+
+    /// Parses the value of a key or sequence item, `rest` being the text after
+    /// the `:` or `-` on the current line and `parent` its indentation.
+    /// Nested sequence items must be indented at least `sequence_indent`.
+    fn value(
+        &mut self,
+        rest: &'a str,
+        parent: usize,
+        sequence_indent: usize,
+    ) -> Result<Vec<String>, YamlParseError> {
+        let mut rest = strip_comment(rest).trim();
+
+        let mut anchor = None;
+        if let Some(stripped) = rest.strip_prefix('&') {
+            let end = stripped.find(char::is_whitespace).unwrap_or(stripped.len());
+            anchor = Some(stripped[..end].to_string());
+            rest = stripped[end..].trim();
+        }
+
+        let lines = if rest.is_empty() {
+            self.block_sequence(sequence_indent)?
+        } else if let Some(alias) = rest.strip_prefix('*') {
+            self.anchors
+                .get(alias.trim())
+                .cloned()
+                .ok_or_else(|| self.error(&format!("unknown alias `{}`", alias.trim())))?
+        } else if rest.starts_with('[') {
+            self.flow_sequence(rest)?
+        } else if rest.starts_with(['|', '>']) {
+            vec![self.block_scalar(rest, parent)?]
+        } else if rest.starts_with(['"', '\'']) {
+            let (scalar, trailing) = self.quoted(rest)?;
+            if !strip_comment(trailing).trim().is_empty() {
+                return Err(self.error("unexpected text after quoted scalar"));
+            }
+            vec![scalar]
+        } else {
+            vec![self.plain_scalar(rest, parent)]
+        };
+
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, lines.clone());
+        }
+        Ok(lines)
+    }
+
+    fn block_sequence(&mut self, min_indent: usize) -> Result<Vec<String>, YamlParseError> {
+        let mut lines = Vec::new();
+        let mut item_indent = None;
+        while let Some(line) = self.next_content_line() {
+            let line_indent = indent(line);
+            let trimmed = line.trim_start();
+            let is_item = trimmed == "-" || trimmed.starts_with("- ");
+            if !is_item
+                || line_indent < min_indent
+                || *item_indent.get_or_insert(line_indent) != line_indent
+            {
+                break;
+            }
+            self.pos += 1;
+            lines.extend(self.value(&trimmed[1..], line_indent, line_indent + 1)?);
+        }
+        Ok(lines)
+    }
+
+    fn flow_sequence(&mut self, rest: &'a str) -> Result<Vec<String>, YamlParseError> {
+        let mut source = rest[1..].to_string();
+        while !source.trim_end().ends_with(']') {
+            let line = self
+                .lines
+                .get(self.pos)
+                .ok_or_else(|| self.error("unterminated flow sequence"))?;
+            self.pos += 1;
+            source.push(' ');
+            source.push_str(strip_comment(line).trim());
+        }
+        let source = source.trim_end();
+        let mut body = source[..source.len() - 1].trim();
+
+        let mut lines = Vec::new();
+        while !body.is_empty() {
+            let (item, rest) = if body.starts_with(['"', '\'']) {
+                let (item, rest) = self.quoted(body)?;
+                (item, rest.trim_start())
+            } else {
+                let end = body.find(',').unwrap_or(body.len());
+                if body[..end].contains(['[', '{']) {
+                    return Err(self.error("nested collections are not supported"));
+                }
+                (body[..end].trim().to_string(), &body[end..])
+            };
+            lines.push(item);
+            body = match rest.strip_prefix(',') {
+                Some(rest) => rest.trim_start(),
+                None if rest.is_empty() => rest,
+                None => return Err(self.error("expected `,` in flow sequence")),
+            };
+        }
+        Ok(lines)
+    }
+
+    fn block_scalar(&mut self, header: &str, parent: usize) -> Result<String, YamlParseError> {
+        let folded = header.starts_with('>');
+        let chomp = if header.contains('-') {
+            Chomp::Strip
+        } else if header.contains('+') {
+            Chomp::Keep
+        } else {
+            Chomp::Clip
+        };
+
+        let mut content_indent = None;
+        let mut raw = Vec::new();
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.trim().is_empty() {
+                raw.push("");
+                self.pos += 1;
+                continue;
+            }
+            let line_indent = indent(line);
+            if line_indent <= parent {
+                break;
+            }
+            let content_indent = *content_indent.get_or_insert(line_indent);
+            if line_indent < content_indent {
+                break;
+            }
+            raw.push(&line[content_indent..]);
+            self.pos += 1;
+        }
+
+        let trailing = raw.iter().rev().take_while(|l| l.is_empty()).count();
+        let body = &raw[..raw.len() - trailing];
+        let mut text = String::new();
+        for (i, line) in body.iter().enumerate() {
+            if i > 0 {
+                let previous_blank = body[i - 1].is_empty();
+                if !folded || line.is_empty() || previous_blank {
+                    text.push('\n');
+                } else {
+                    text.push(' ');
+                }
+            }
+            text.push_str(line);
+        }
+        match chomp {
+            Chomp::Strip => {}
+            Chomp::Clip if !text.is_empty() => text.push('\n'),
+            Chomp::Clip => {}
+            Chomp::Keep => text.push_str(&"\n".repeat(trailing + 1)),
+        }
+        Ok(text)
+    }
+
+    /// Reads a plain scalar, folding more-indented continuation lines into it.
+    fn plain_scalar(&mut self, first: &str, parent: usize) -> String {
+        let mut text = first.to_string();
+        while let Some(line) = self.lines.get(self.pos) {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || indent(line) <= parent || trimmed.starts_with("- ") {
+                break;
+            }
+            text.push(' ');
+            text.push_str(strip_comment(trimmed).trim());
+            self.pos += 1;
+        }
+        text
+    }
+
+    /// Reads a quoted scalar at the start of `s`, returning it and the rest of `s`.
+    fn quoted<'s>(&self, s: &'s str) -> Result<(String, &'s str), YamlParseError> {
+        let quote = s.chars().next().unwrap_or('"');
+        let mut text = String::new();
+        let mut chars = s.char_indices().skip(1).peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == quote {
+                if quote == '\'' && matches!(chars.peek(), Some((_, '\''))) {
+                    chars.next();
+                    text.push('\'');
+                    continue;
+                }
+                return Ok((text, &s[i + 1..]));
+            }
+            if c == '\\' && quote == '"' {
+                let (_, escape) = chars
+                    .next()
+                    .ok_or_else(|| self.error("unterminated escape"))?;
+                match escape {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    'r' => text.push('\r'),
+                    '0' => text.push('\0'),
+                    'x' | 'u' | 'U' => {
+                        let len = match escape {
+                            'x' => 2,
+                            'u' => 4,
+                            _ => 8,
+                        };
+                        let hex: String = (0..len)
+                            .filter_map(|_| chars.next().map(|(_, c)| c))
+                            .collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| {
+                                self.error(&format!("invalid escape `\\{}{}`", escape, hex))
+                            })?;
+                        text.push(c);
+                    }
+                    other => text.push(other),
+                }
+                continue;
+            }
+            text.push(c);
+        }
+        Err(self.error("unterminated quoted scalar"))
+    }
+}
+
+enum Chomp {
+    Strip,
+    Clip,
+    Keep,
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Removes a trailing ` # comment` from unquoted text.
+fn strip_comment(s: &str) -> &str {
+    let mut in_quote = None;
+    let mut previous = ' ';
+    for (i, c) in s.char_indices() {
+        match in_quote {
+            Some(q) if c == q => in_quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && previous.is_whitespace() => in_quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return &s[..i],
+            None => {}
+        }
+        previous = c;
+    }
+    s
+}
+
+/// Multi-line text without surrounding or trailing whitespace round-trips as `|-`.
+fn is_block_safe(line: &str) -> bool {
+    line.contains('\n')
+        && !line.starts_with([' ', '\n'])
+        && !line.ends_with('\n')
+        && line.lines().all(|l| l == l.trim_end() && !l.contains('\t'))
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_yaml() {
+        let line_pairs = parse(
+            "# Dungeon lines\n\
+             ---\n\
+             meta.welcome: Welcome! # a comment\n\
+             combat.encounter: &encounters\n\
+             \x20 - You encounter a lion!\n\
+             \x20 - \"Oh no! It's a \\\"bear\\\"!\"\n\
+             \x20 - 'It''s a tiger!'\n\
+             \x20 - |-\n\
+             \x20   A dragon lands.\n\
+             \x20   It looks hungry.\n\
+             \x20 - >\n\
+             \x20   A wolf\n\
+             \x20   howls.\n\
+             combat.ambush: *encounters\n\
+             \"quoted.key\": [one, \"two, three\"]\n",
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert!(line_pairs["meta.welcome"].contains("Welcome!"));
+        let encounters = &line_pairs["combat.encounter"];
+        assert_eq!(encounters.len(), 5);
+        assert!(encounters.contains("Oh no! It's a \"bear\"!"));
+        assert!(encounters.contains("It's a tiger!"));
+        assert!(encounters.contains("A dragon lands.\nIt looks hungry."));
+        assert!(encounters.contains("A wolf howls.\n"));
+        assert_eq!(&line_pairs["combat.ambush"], encounters);
+        assert_eq!(line_pairs["quoted.key"].len(), 2);
+        assert!(line_pairs["quoted.key"].contains("two, three"));
+    }
+
+    #[test]
+    fn parse_yaml_errors() {
+        assert_eq!(
+            parse("a:\n  - x\nb: *missing\n", &mut Vec::new())
+                .unwrap_err()
+                .line,
+            3
+        );
+        assert_eq!(parse("a: \"open\n", &mut Vec::new()).unwrap_err().line, 1);
+        assert!(parse("  a: b\n", &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let line_pairs = parse(
+            "a:\n  - plain\n  - \"with: colon # and hash\"\n  - |-\n    two\n    lines\nb: \"tab\\there\"\n",
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            parse(&to_string(&line_pairs), &mut Vec::new()).unwrap(),
+            line_pairs
+        );
+    }
+}
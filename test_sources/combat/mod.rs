@@ -0,0 +1,9 @@
+use hottext::HotText;
+use rand::Rng;
+
+pub fn fight<R: Rng>(ht: &mut HotText<R>) {
+    println!("{}", ht.get_line("combat.encounter").unwrap());
+    let data = vec![("weapon", "sword"), ("enemy", "the lion")];
+    println!("{}", ht.render_line("combat.slay", data).unwrap());
+    println!("{}", ht.get_line_or("combat.flee", "You run away."));
+}
@@ -0,0 +1,10 @@
+mod combat;
+
+use hottext::HotText;
+
+fn main() {
+    let mut ht = HotText::new(rand::thread_rng());
+    ht.load("lines.toml").unwrap();
+    println!("{}", ht.get_line("meta.welcome").unwrap());
+    combat::fight(&mut ht);
+}